@@ -4,11 +4,13 @@ mod models;
 
 use models::*;
 use sprite_dicing::{
-    Artifacts, DicedSprite, Error, Pivot, Pixel, Prefs, Progress, Rect, SourceSprite, Texture, Uv,
-    Vertex,
+    AlphaCrop, Artifacts, AtlasCell, AtlasStats, Border, CellRef, Coordinates, DicedSprite, Error,
+    Pivot, Pixel, Prefs, Progress, Rect, Slice, SourceSprite, SpriteStats, Stats, Texture, URect,
+    UnitOrder, Uv, UvOrigin, Vertex, Winding, YAxis,
 };
 use std::ffi::{c_char, CStr, CString};
 use std::mem;
+use std::ops::Range;
 
 /// C ABI wrapper over [sprite_dicing::dice].
 ///
@@ -32,16 +34,30 @@ fn to_prefs(c: CPrefs) -> Prefs {
     Prefs {
         unit_size: c.unit_size,
         padding: c.padding,
+        block_alignment: c.block_alignment,
+        grow_padding: c.grow_padding,
+        mip_levels: c.mip_levels,
         uv_inset: c.uv_inset,
         trim_transparent: c.trim_transparent,
+        alpha_crop: to_alpha_crop(c.alpha_crop),
+        outline_tolerance: if c.has_outline_tolerance {
+            Some(c.outline_tolerance)
+        } else {
+            None
+        },
+        dedupe_tints: c.dedupe_tints,
+        alias_duplicates: c.alias_duplicates,
         atlas_size_limit: c.atlas_size_limit,
         atlas_square: c.atlas_square,
         atlas_pot: c.atlas_pot,
+        unit_order: to_unit_order(c.unit_order),
+        collect_stats: c.collect_stats,
         ppu: c.ppu,
         pivot: Pivot {
             x: c.pivot.x,
             y: c.pivot.y,
         },
+        coordinates: to_coordinates(c.coordinates),
         on_progress: if c.has_progress_callback {
             Some(Box::new(move |p| unsafe {
                 (c.progress_callback)(to_c_progress(p))
@@ -49,24 +65,83 @@ fn to_prefs(c: CPrefs) -> Prefs {
         } else {
             None
         },
+        on_cancel: if c.has_cancel_callback {
+            Some(Box::new(move || unsafe { (c.cancel_callback)() }))
+        } else {
+            None
+        },
+    }
+}
+
+fn to_alpha_crop(c: u8) -> AlphaCrop {
+    match c {
+        1 => AlphaCrop::Rect,
+        2 => AlphaCrop::Polygon,
+        _ => AlphaCrop::Off,
+    }
+}
+
+fn to_unit_order(c: u8) -> UnitOrder {
+    match c {
+        1 => UnitOrder::Source,
+        2 => UnitOrder::Color,
+        _ => UnitOrder::Hash,
+    }
+}
+
+fn to_coordinates(c: CCoordinates) -> Coordinates {
+    Coordinates {
+        y_axis: match c.y_axis {
+            1 => YAxis::Up,
+            _ => YAxis::Down,
+        },
+        uv_origin: match c.uv_origin {
+            1 => UvOrigin::BottomLeft,
+            _ => UvOrigin::TopLeft,
+        },
+        winding: match c.winding {
+            1 => Winding::Clockwise,
+            _ => Winding::CounterClockwise,
+        },
     }
 }
 
 fn to_c_err(e: Error) -> CResult {
     let error = to_c_str(&e.to_string());
+    let code = e.code();
     let ok = CArtifacts {
         atlases: CSlice::empty(),
+        mips: CSlice::empty(),
+        cells: CSlice::empty(),
         sprites: CSlice::empty(),
+        has_stats: false,
+        stats: CStats::empty(),
     };
-    CResult { error, ok }
+    CResult { error, code, ok }
 }
 
 fn to_c_ok(arts: Artifacts) -> CResult {
     let atlases = to_c_slice(arts.atlases.iter().map(to_c_texture).collect());
+    let mips = to_c_slice(arts.mips.iter().flatten().map(to_c_texture).collect());
+    let cells = arts
+        .cells
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, cells)| cells.iter().map(move |cell| to_c_atlas_cell(idx, cell)));
+    let cells = to_c_slice(cells.collect());
     let sprites = to_c_slice(arts.sprites.iter().map(to_c_sprite).collect());
+    let stats = arts.stats.as_ref().map_or(CStats::empty(), to_c_stats);
     CResult {
         error: to_c_str(""),
-        ok: CArtifacts { atlases, sprites },
+        code: 0,
+        ok: CArtifacts {
+            atlases,
+            mips,
+            cells,
+            sprites,
+            has_stats: arts.stats.is_some(),
+            stats,
+        },
     }
 }
 
@@ -82,6 +157,32 @@ unsafe fn to_sprite(c: &CSourceSprite) -> SourceSprite {
         } else {
             None
         },
+        metadata: to_slice(c.metadata)
+            .iter()
+            .map(|e| (to_str(e.key).to_owned(), to_str(e.value).to_owned()))
+            .collect(),
+        border: if c.has_border {
+            Some(Border::new(
+                c.border.left,
+                c.border.right,
+                c.border.top,
+                c.border.bottom,
+            ))
+        } else {
+            None
+        },
+        ppu: if c.has_ppu { Some(c.ppu) } else { None },
+        trim_transparent: if c.has_trim_transparent {
+            Some(c.trim_transparent)
+        } else {
+            None
+        },
+        padding: if c.has_padding { Some(c.padding) } else { None },
+        sequence: if c.has_sequence {
+            Some(to_str(c.sequence).to_owned())
+        } else {
+            None
+        },
     }
 }
 
@@ -92,8 +193,111 @@ fn to_c_sprite(sprite: &DicedSprite) -> CDicedSprite {
         vertices: to_c_slice(sprite.vertices.iter().map(to_c_vertex).collect()),
         uvs: to_c_slice(sprite.uvs.iter().map(to_c_uv).collect()),
         indices: to_c_slice(sprite.indices.iter().map(|i| *i as u64).collect()),
+        opaque_indices: to_c_range(&sprite.opaque_indices),
+        translucent_indices: to_c_range(&sprite.translucent_indices),
         rect: to_c_rect(&sprite.rect),
         pivot: to_c_pivot(&sprite.pivot),
+        source_width: sprite.source_size.width,
+        source_height: sprite.source_size.height,
+        pixel_rect: to_c_urect(&sprite.pixel_rect),
+        pixel_pivot: to_c_pivot(&sprite.pixel_pivot),
+        metadata: to_c_slice(sprite.metadata.iter().map(to_c_metadata_entry).collect()),
+        has_border: sprite.border.is_some(),
+        border: sprite.border.map(to_c_border).unwrap_or_default(),
+        slices: to_c_slice(sprite.slices.iter().map(to_c_slice_region).collect()),
+        outlines: to_c_slice(sprite.outlines.iter().map(|o| to_c_outline(o)).collect()),
+        colors: to_c_slice(sprite.colors.iter().map(to_c_pixel).collect()),
+        has_alias: sprite.alias_of.is_some(),
+        alias_of: to_c_str(sprite.alias_of.as_deref().unwrap_or_default()),
+    }
+}
+
+fn to_c_outline(outline: &[Vertex]) -> CSlice<CVertex> {
+    to_c_slice(outline.iter().map(to_c_vertex).collect())
+}
+
+fn to_c_range(range: &Range<usize>) -> CRange {
+    CRange {
+        start: range.start as u64,
+        end: range.end as u64,
+    }
+}
+
+fn to_c_border(border: Border) -> CBorder {
+    CBorder {
+        left: border.left,
+        right: border.right,
+        top: border.top,
+        bottom: border.bottom,
+    }
+}
+
+fn to_c_slice_region(slice: &Slice) -> u8 {
+    match slice {
+        Slice::TopLeft => 0,
+        Slice::Top => 1,
+        Slice::TopRight => 2,
+        Slice::Left => 3,
+        Slice::Center => 4,
+        Slice::Right => 5,
+        Slice::BottomLeft => 6,
+        Slice::Bottom => 7,
+        Slice::BottomRight => 8,
+    }
+}
+
+fn to_c_metadata_entry((key, value): (&String, &String)) -> CMetadataEntry {
+    CMetadataEntry {
+        key: to_c_str(key),
+        value: to_c_str(value),
+    }
+}
+
+fn to_c_atlas_cell(atlas_index: usize, cell: &AtlasCell) -> CAtlasCell {
+    CAtlasCell {
+        atlas_index: atlas_index as u64,
+        hash: cell.hash,
+        rect: to_c_urect(&cell.rect),
+        refs: to_c_slice(cell.refs.iter().map(to_c_cell_ref).collect()),
+    }
+}
+
+fn to_c_cell_ref(cell_ref: &CellRef) -> CCellRef {
+    CCellRef {
+        sprite_id: to_c_str(&cell_ref.sprite_id),
+        rect: to_c_urect(&cell_ref.rect),
+    }
+}
+
+fn to_c_stats(stats: &Stats) -> CStats {
+    CStats {
+        sprites: to_c_slice(stats.sprites.iter().map(to_c_sprite_stats).collect()),
+        atlases: to_c_slice(stats.atlases.iter().map(to_c_atlas_stats).collect()),
+        source_bytes: stats.source_bytes as u64,
+        atlas_bytes: stats.atlas_bytes as u64,
+        mesh_bytes: stats.mesh_bytes as u64,
+    }
+}
+
+fn to_c_sprite_stats(stats: &SpriteStats) -> CSpriteStats {
+    CSpriteStats {
+        id: to_c_str(&stats.id),
+        units: stats.units as u64,
+        unique: stats.unique as u64,
+        shared: stats.shared as u64,
+        vertices: stats.vertices as u64,
+        indices: stats.indices as u64,
+        mesh_bytes: stats.mesh_bytes as u64,
+    }
+}
+
+fn to_c_atlas_stats(stats: &AtlasStats) -> CAtlasStats {
+    CAtlasStats {
+        width: stats.size.width,
+        height: stats.size.height,
+        units: stats.units as u64,
+        occupancy: stats.occupancy,
+        wasted_texels: stats.wasted_texels as u64,
     }
 }
 
@@ -143,6 +347,15 @@ fn to_c_rect(rect: &Rect) -> CRect {
     }
 }
 
+fn to_c_urect(rect: &URect) -> CURect {
+    CURect {
+        x: rect.x,
+        y: rect.y,
+        width: rect.width,
+        height: rect.height,
+    }
+}
+
 fn to_c_pivot(p: &Pivot) -> CPivot {
     CPivot { x: p.x, y: p.y }
 }
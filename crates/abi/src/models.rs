@@ -8,6 +8,24 @@ pub struct CSourceSprite {
     pub texture: CTexture,
     pub has_pivot: bool,
     pub pivot: CPivot,
+    pub metadata: CSlice<CMetadataEntry>,
+    pub has_border: bool,
+    pub border: CBorder,
+    pub has_ppu: bool,
+    pub ppu: f32,
+    pub has_trim_transparent: bool,
+    pub trim_transparent: bool,
+    pub has_padding: bool,
+    pub padding: u32,
+    pub has_sequence: bool,
+    pub sequence: *const c_char,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CMetadataEntry {
+    pub key: *const c_char,
+    pub value: *const c_char,
 }
 
 #[repr(C)]
@@ -32,21 +50,43 @@ pub struct CPixel {
 pub struct CPrefs {
     pub unit_size: u32,
     pub padding: u32,
+    pub block_alignment: u32,
+    pub grow_padding: bool,
+    pub mip_levels: u32,
     pub uv_inset: f32,
     pub trim_transparent: bool,
+    pub alpha_crop: u8,
+    pub has_outline_tolerance: bool,
+    pub outline_tolerance: f32,
+    pub dedupe_tints: bool,
+    pub alias_duplicates: bool,
     pub atlas_size_limit: u32,
     pub atlas_square: bool,
     pub atlas_pot: bool,
+    pub unit_order: u8,
+    pub collect_stats: bool,
     pub ppu: f32,
     pub pivot: CPivot,
+    pub coordinates: CCoordinates,
     pub has_progress_callback: bool,
     pub progress_callback: unsafe extern "C" fn(CProgress),
+    pub has_cancel_callback: bool,
+    pub cancel_callback: unsafe extern "C" fn() -> bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CCoordinates {
+    pub y_axis: u8,
+    pub uv_origin: u8,
+    pub winding: u8,
 }
 
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub struct CResult {
     pub error: *const c_char,
+    pub code: u32,
     pub ok: CArtifacts,
 }
 
@@ -54,7 +94,61 @@ pub struct CResult {
 #[derive(Clone, Copy)]
 pub struct CArtifacts {
     pub atlases: CSlice<CTexture>,
+    /// Mip chains of all the atlases, flattened in atlas order.
+    pub mips: CSlice<CTexture>,
+    /// Placed unit cells of all the atlases, flattened in atlas order.
+    pub cells: CSlice<CAtlasCell>,
     pub sprites: CSlice<CDicedSprite>,
+    pub has_stats: bool,
+    pub stats: CStats,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CAtlasCell {
+    pub atlas_index: u64,
+    pub hash: u64,
+    pub rect: CURect,
+    pub refs: CSlice<CCellRef>,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CCellRef {
+    pub sprite_id: *const c_char,
+    pub rect: CURect,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CStats {
+    pub sprites: CSlice<CSpriteStats>,
+    pub atlases: CSlice<CAtlasStats>,
+    pub source_bytes: u64,
+    pub atlas_bytes: u64,
+    pub mesh_bytes: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CSpriteStats {
+    pub id: *const c_char,
+    pub units: u64,
+    pub unique: u64,
+    pub shared: u64,
+    pub vertices: u64,
+    pub indices: u64,
+    pub mesh_bytes: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CAtlasStats {
+    pub width: u32,
+    pub height: u32,
+    pub units: u64,
+    pub occupancy: f32,
+    pub wasted_texels: u64,
 }
 
 #[repr(C)]
@@ -65,8 +159,22 @@ pub struct CDicedSprite {
     pub vertices: CSlice<CVertex>,
     pub uvs: CSlice<CUv>,
     pub indices: CSlice<u64>,
+    pub opaque_indices: CRange,
+    pub translucent_indices: CRange,
     pub rect: CRect,
     pub pivot: CPivot,
+    pub source_width: u32,
+    pub source_height: u32,
+    pub pixel_rect: CURect,
+    pub pixel_pivot: CPivot,
+    pub metadata: CSlice<CMetadataEntry>,
+    pub has_border: bool,
+    pub border: CBorder,
+    pub slices: CSlice<u8>,
+    pub outlines: CSlice<CSlice<CVertex>>,
+    pub colors: CSlice<CPixel>,
+    pub has_alias: bool,
+    pub alias_of: *const c_char,
 }
 
 #[repr(C)]
@@ -92,6 +200,31 @@ pub struct CRect {
     pub height: f32,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CURect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct CBorder {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub struct CPivot {
@@ -113,6 +246,18 @@ pub struct CSlice<T> {
     pub len: u64,
 }
 
+impl CStats {
+    pub const fn empty() -> CStats {
+        CStats {
+            sprites: CSlice::empty(),
+            atlases: CSlice::empty(),
+            source_bytes: 0,
+            atlas_bytes: 0,
+            mesh_bytes: 0,
+        }
+    }
+}
+
 impl<T> CSlice<T> {
     pub const fn empty() -> CSlice<T> {
         CSlice {
@@ -1,4 +1,4 @@
-use sprite_dicing::DicedSprite;
+use sprite_dicing::{AtlasStats, DicedSprite, Slice, SpriteStats, Stats, Vertex};
 
 /// Serializes specified diced sprites to JSON string.
 pub fn sprites_to_json(sprites: &[DicedSprite]) -> String {
@@ -12,14 +12,9 @@ pub fn sprites_to_json(sprites: &[DicedSprite]) -> String {
 }
 
 fn sprite_to_json(sprite: &DicedSprite) -> String {
-    let id = &sprite.id;
+    let id = escape(&sprite.id);
     let atlas = sprite.atlas_index;
-    let vertices = sprite
-        .vertices
-        .iter()
-        .map(|v| format!(r#"{{ "x": {}, "y": {} }}"#, v.x, v.y))
-        .collect::<Vec<_>>()
-        .join(", ");
+    let vertices = vertices_to_json(&sprite.vertices);
     let uvs = sprite
         .uvs
         .iter()
@@ -36,6 +31,49 @@ fn sprite_to_json(sprite: &DicedSprite) -> String {
     let y = sprite.rect.y;
     let width = sprite.rect.width;
     let height = sprite.rect.height;
+    let opaque = &sprite.opaque_indices;
+    let translucent = &sprite.translucent_indices;
+    let size = &sprite.source_size;
+    let px = &sprite.pixel_rect;
+    let pivot = &sprite.pixel_pivot;
+    let metadata = sprite
+        .metadata
+        .iter()
+        .map(|(k, v)| format!(r#""{}": "{}""#, escape(k), escape(v)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let outlines = sprite
+        .outlines
+        .iter()
+        .map(|o| format!("[{}]", vertices_to_json(o)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let colors = sprite
+        .colors
+        .iter()
+        .map(|c| {
+            let [r, g, b, a] = c.to_raw();
+            format!(r#"{{ "r": {r}, "g": {g}, "b": {b}, "a": {a} }}"#)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let border = match &sprite.border {
+        Some(b) => format!(
+            r#"{{ "left": {}, "right": {}, "top": {}, "bottom": {} }}"#,
+            b.left, b.right, b.top, b.bottom
+        ),
+        None => "null".to_owned(),
+    };
+    let slices = sprite
+        .slices
+        .iter()
+        .map(|s| format!(r#""{}""#, slice_to_json(s)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let alias_of = match &sprite.alias_of {
+        Some(id) => format!(r#""{}""#, escape(id)),
+        None => "null".to_owned(),
+    };
 
     format!(
         r#"
@@ -45,15 +83,132 @@ fn sprite_to_json(sprite: &DicedSprite) -> String {
         "vertices": [{vertices}],
         "uvs": [{uvs}],
         "indices": [{indices}],
-        "rect": {{ "x": {x}, "y": {y}, "width": {width}, "height": {height} }}
-    }}"#
+        "opaqueIndices": {{ "start": {}, "end": {} }},
+        "translucentIndices": {{ "start": {}, "end": {} }},
+        "rect": {{ "x": {x}, "y": {y}, "width": {width}, "height": {height} }},
+        "sourceSize": {{ "width": {}, "height": {} }},
+        "pixelRect": {{ "x": {}, "y": {}, "width": {}, "height": {} }},
+        "pixelPivot": {{ "x": {}, "y": {} }},
+        "border": {border},
+        "slices": [{slices}],
+        "metadata": {{{metadata}}},
+        "outlines": [{outlines}],
+        "colors": [{colors}],
+        "aliasOf": {alias_of}
+    }}"#,
+        opaque.start,
+        opaque.end,
+        translucent.start,
+        translucent.end,
+        size.width,
+        size.height,
+        px.x,
+        px.y,
+        px.width,
+        px.height,
+        pivot.x,
+        pivot.y
+    )
+}
+
+fn vertices_to_json(vertices: &[Vertex]) -> String {
+    vertices
+        .iter()
+        .map(|v| format!(r#"{{ "x": {}, "y": {} }}"#, v.x, v.y))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn slice_to_json(slice: &Slice) -> &'static str {
+    match slice {
+        Slice::TopLeft => "topLeft",
+        Slice::Top => "top",
+        Slice::TopRight => "topRight",
+        Slice::Left => "left",
+        Slice::Center => "center",
+        Slice::Right => "right",
+        Slice::BottomLeft => "bottomLeft",
+        Slice::Bottom => "bottom",
+        Slice::BottomRight => "bottomRight",
+    }
+}
+
+fn escape(str: &str) -> String {
+    let mut escaped = String::with_capacity(str.len());
+    for char in str.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Serializes specified dicing statistics to JSON string.
+pub fn stats_to_json(stats: &Stats) -> String {
+    let sprites = stats
+        .sprites
+        .iter()
+        .map(sprite_stats_to_json)
+        .collect::<Vec<_>>()
+        .join(",");
+    let atlases = stats
+        .atlases
+        .iter()
+        .map(atlas_stats_to_json)
+        .collect::<Vec<_>>()
+        .join(",");
+    let source = stats.source_bytes;
+    let atlas = stats.atlas_bytes;
+    let mesh = stats.mesh_bytes;
+    let ratio = stats.compression_ratio();
+
+    format!(
+        r#"{{
+    "sprites": [{sprites}
+    ],
+    "atlases": [{atlases}
+    ],
+    "sourceBytes": {source},
+    "atlasBytes": {atlas},
+    "meshBytes": {mesh},
+    "compressionRatio": {ratio}
+}}
+"#
+    )
+}
+
+fn sprite_stats_to_json(stats: &SpriteStats) -> String {
+    format!(
+        r#"
+        {{ "id": "{}", "units": {}, "unique": {}, "shared": {}, "vertices": {}, "indices": {}, "meshBytes": {} }}"#,
+        escape(&stats.id),
+        stats.units,
+        stats.unique,
+        stats.shared,
+        stats.vertices,
+        stats.indices,
+        stats.mesh_bytes
+    )
+}
+
+fn atlas_stats_to_json(stats: &AtlasStats) -> String {
+    format!(
+        r#"
+        {{ "width": {}, "height": {}, "units": {}, "occupancy": {}, "wastedTexels": {} }}"#,
+        stats.size.width, stats.size.height, stats.units, stats.occupancy, stats.wasted_texels
     )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sprite_dicing::{Pivot, Rect, Uv, Vertex};
+    use sprite_dicing::{Border, Metadata, Pivot, Pixel, Rect, URect, USize, Uv, Vertex};
 
     #[test]
     fn builds_sprites_json() {
@@ -64,8 +219,23 @@ mod tests {
                 vertices: vec![Vertex::new(1.0, -2.0), Vertex::new(-3.0, 4.525)],
                 uvs: vec![Uv::new(0.1, 0.2), Uv::new(0.3, 0.4)],
                 indices: vec![1, 2, 3],
+                opaque_indices: 0..0,
+                translucent_indices: 0..3,
                 rect: Rect::new(0.5, 0.5, 100.0, 50.0),
                 pivot: Pivot::new(0.0, 0.0),
+                source_size: USize::new(120, 60),
+                pixel_rect: URect::new(5, 5, 100, 50),
+                pixel_pivot: Pivot::new(0.0, 0.0),
+                metadata: Metadata::from([
+                    ("tag".to_owned(), "hero".to_owned()),
+                    ("quote".to_owned(), "\"a\\b\"\n".to_owned()),
+                ]),
+                border: Some(Border::new(1, 2, 3, 4)),
+                slices: vec![Slice::TopLeft, Slice::BottomRight],
+                outlines: vec![vec![Vertex::new(0.0, 0.0), Vertex::new(0.5, 1.0)]],
+                colors: vec![Pixel::new(255, 128, 0, 255), Pixel::new(1, 2, 3, 4)],
+                atlas_indices: vec![],
+                alias_of: None,
             },
             DicedSprite {
                 id: "img".to_owned(),
@@ -73,8 +243,20 @@ mod tests {
                 vertices: vec![Vertex::new(-1.0, 2.0)],
                 uvs: vec![Uv::new(0.01, 0.02)],
                 indices: vec![0],
+                opaque_indices: 0..1,
+                translucent_indices: 1..1,
                 rect: Rect::new(-1.5, 0.0, 0.0, 10.10),
                 pivot: Pivot::new(0.0, 0.0),
+                source_size: USize::new(0, 10),
+                pixel_rect: URect::new(0, 0, 0, 10),
+                pixel_pivot: Pivot::new(0.0, 5.5),
+                metadata: Metadata::new(),
+                border: None,
+                slices: vec![],
+                outlines: vec![],
+                colors: vec![],
+                atlas_indices: vec![],
+                alias_of: Some("foo/bar/img".to_owned()),
             },
         ];
         assert_eq!(
@@ -86,7 +268,18 @@ mod tests {
         "vertices": [{ "x": 1, "y": -2 }, { "x": -3, "y": 4.525 }],
         "uvs": [{ "u": 0.1, "v": 0.2 }, { "u": 0.3, "v": 0.4 }],
         "indices": [1, 2, 3],
-        "rect": { "x": 0.5, "y": 0.5, "width": 100, "height": 50 }
+        "opaqueIndices": { "start": 0, "end": 0 },
+        "translucentIndices": { "start": 0, "end": 3 },
+        "rect": { "x": 0.5, "y": 0.5, "width": 100, "height": 50 },
+        "sourceSize": { "width": 120, "height": 60 },
+        "pixelRect": { "x": 5, "y": 5, "width": 100, "height": 50 },
+        "pixelPivot": { "x": 0, "y": 0 },
+        "border": { "left": 1, "right": 2, "top": 3, "bottom": 4 },
+        "slices": ["topLeft", "bottomRight"],
+        "metadata": {"quote": "\"a\\b\"\n", "tag": "hero"},
+        "outlines": [[{ "x": 0, "y": 0 }, { "x": 0.5, "y": 1 }]],
+        "colors": [{ "r": 255, "g": 128, "b": 0, "a": 255 }, { "r": 1, "g": 2, "b": 3, "a": 4 }],
+        "aliasOf": null
     },
     {
         "id": "img",
@@ -94,10 +287,107 @@ mod tests {
         "vertices": [{ "x": -1, "y": 2 }],
         "uvs": [{ "u": 0.01, "v": 0.02 }],
         "indices": [0],
-        "rect": { "x": -1.5, "y": 0, "width": 0, "height": 10.1 }
+        "opaqueIndices": { "start": 0, "end": 1 },
+        "translucentIndices": { "start": 1, "end": 1 },
+        "rect": { "x": -1.5, "y": 0, "width": 0, "height": 10.1 },
+        "sourceSize": { "width": 0, "height": 10 },
+        "pixelRect": { "x": 0, "y": 0, "width": 0, "height": 10 },
+        "pixelPivot": { "x": 0, "y": 5.5 },
+        "border": null,
+        "slices": [],
+        "metadata": {},
+        "outlines": [],
+        "colors": [],
+        "aliasOf": "foo/bar/img"
     }
 ]
 "#
         );
     }
+
+    #[test]
+    fn builds_stats_json() {
+        let stats = Stats {
+            sprites: vec![SpriteStats {
+                id: "foo/img".to_owned(),
+                units: 4,
+                unique: 3,
+                shared: 1,
+                vertices: 16,
+                indices: 24,
+                mesh_bytes: 448,
+            }],
+            atlases: vec![AtlasStats {
+                size: USize::new(4, 2),
+                units: 3,
+                occupancy: 0.75,
+                wasted_texels: 2,
+            }],
+            source_bytes: 1024,
+            atlas_bytes: 32,
+            mesh_bytes: 480,
+        };
+        assert_eq!(
+            stats_to_json(&stats),
+            r#"{
+    "sprites": [
+        { "id": "foo/img", "units": 4, "unique": 3, "shared": 1, "vertices": 16, "indices": 24, "meshBytes": 448 }
+    ],
+    "atlases": [
+        { "width": 4, "height": 2, "units": 3, "occupancy": 0.75, "wastedTexels": 2 }
+    ],
+    "sourceBytes": 1024,
+    "atlasBytes": 32,
+    "meshBytes": 480,
+    "compressionRatio": 2
+}
+"#
+        );
+    }
+
+    #[test]
+    fn escapes_sprite_ids() {
+        let json = sprites_to_json(&[sprite(r#"a"b\c"#)]);
+        assert!(json.contains(r#""id": "a\"b\\c","#));
+        let stats = Stats {
+            sprites: vec![SpriteStats {
+                id: r#"a"b\c"#.to_owned(),
+                units: 0,
+                unique: 0,
+                shared: 0,
+                vertices: 0,
+                indices: 0,
+                mesh_bytes: 0,
+            }],
+            atlases: vec![],
+            source_bytes: 0,
+            atlas_bytes: 0,
+            mesh_bytes: 0,
+        };
+        assert!(stats_to_json(&stats).contains(r#"{ "id": "a\"b\\c","#));
+    }
+
+    fn sprite(id: &str) -> DicedSprite {
+        DicedSprite {
+            id: id.to_owned(),
+            atlas_index: 0,
+            vertices: vec![],
+            uvs: vec![],
+            indices: vec![],
+            opaque_indices: 0..0,
+            translucent_indices: 0..0,
+            rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            pivot: Pivot::new(0.0, 0.0),
+            source_size: USize::new(1, 1),
+            pixel_rect: URect::new(0, 0, 1, 1),
+            pixel_pivot: Pivot::new(0.0, 0.0),
+            metadata: Metadata::new(),
+            border: None,
+            slices: vec![],
+            outlines: vec![],
+            colors: vec![],
+            atlas_indices: vec![],
+            alias_of: None,
+        }
+    }
 }
@@ -1,11 +1,14 @@
 mod img;
 mod json;
+mod meta;
 pub mod models;
+mod text;
 
 use crate::models::*;
 use rayon::prelude::*;
-use sprite_dicing::{DicedSprite, Prefs, Progress, SourceSprite, Texture};
+use sprite_dicing::{DicedSprite, Prefs, Progress, SourceSprite, Stats, Texture};
 use std::{fs, path::Path, path::PathBuf};
+pub use text::stats_to_text;
 
 /// Packs all the textures of supported formats inside directory with specified path and
 /// writes generated atlas textures and diced sprite meshes serialized in JSON.
@@ -16,14 +19,20 @@ use std::{fs, path::Path, path::PathBuf};
 /// * `fs_prefs`: FS-related preferences: out directory, atlas format, etc.
 /// * `prefs`: Dicing-related preferences: unit size, padding, PPU, etc.
 ///
-/// returns: [Ok] when operation successful, [Error] otherwise.
-pub fn dice_dir(dir: &Path, fs_prefs: &FsPrefs, prefs: &Prefs) -> Result<()> {
+/// returns: Statistics of the operation when [Prefs::collect_stats] is enabled (also written
+/// to the out directory in JSON), [Error] when the operation failed.
+pub fn dice_dir(dir: &Path, fs_prefs: &FsPrefs, prefs: &Prefs) -> Result<Option<Stats>> {
     let paths = collect_sources(dir, fs_prefs)?;
     let sources = load_sources(dir, &paths, prefs, fs_prefs)?;
     let diced = sprite_dicing::dice(&sources, prefs).map_err(Error::Dicing)?;
     let out_dir = fs_prefs.out.as_deref().unwrap_or(dir);
     write_atlases(diced.atlases, out_dir, &fs_prefs.atlas_format, prefs)?;
-    write_sprites(diced.sprites, out_dir)
+    write_mips(diced.mips, out_dir, &fs_prefs.atlas_format)?;
+    write_sprites(diced.sprites, out_dir)?;
+    if let Some(stats) = &diced.stats {
+        write_stats(stats, out_dir)?;
+    }
+    Ok(diced.stats)
 }
 
 fn collect_sources(dir: &Path, prefs: &FsPrefs) -> Result<Vec<PathBuf>> {
@@ -47,7 +56,8 @@ fn load_sources(
 ) -> Result<Vec<SourceSprite>> {
     let mut sprites = Vec::with_capacity(paths.len());
     for (idx, path) in paths.iter().enumerate() {
-        Progress::report(prefs, 0, idx, paths.len(), "Decoding source textures");
+        Progress::report(prefs, 0, idx, paths.len(), "Decoding source textures")
+            .map_err(Error::Dicing)?;
         sprites.push(create_sprite(root, path, fs_prefs)?);
     }
     Ok(sprites)
@@ -56,8 +66,11 @@ fn load_sources(
 fn create_sprite(root: &Path, path: &Path, prefs: &FsPrefs) -> Result<SourceSprite> {
     let id = eval_sprite_id(root, path, &prefs.separator);
     let texture = img::to_texture(&img::load(path)?);
-    let pivot = None;
-    Ok(SourceSprite { id, texture, pivot })
+    let metadata = meta::load(path)?;
+    Ok(SourceSprite {
+        metadata,
+        ..SourceSprite::new(id, texture)
+    })
 }
 
 fn eval_sprite_id(root: &Path, path: &Path, separator: &str) -> String {
@@ -70,13 +83,24 @@ fn eval_sprite_id(root: &Path, path: &Path, separator: &str) -> String {
 }
 
 fn write_atlases(tex: Vec<Texture>, dir: &Path, fmt: &AtlasFormat, prefs: &Prefs) -> Result<()> {
-    Progress::report(prefs, 4, 0, tex.len(), "Encoding atlas textures");
+    Progress::report(prefs, 4, 0, tex.len(), "Encoding atlas textures").map_err(Error::Dicing)?;
     tex.into_par_iter().enumerate().try_for_each(|(idx, tex)| {
         let name = format!("atlas_{idx}.{}", fmt.extension());
         write_atlas(&dir.join(name), tex)
     })
 }
 
+fn write_mips(mips: Vec<Vec<Texture>>, dir: &Path, fmt: &AtlasFormat) -> Result<()> {
+    let mips = mips.into_iter().enumerate().flat_map(|(idx, chain)| {
+        let levels = chain.into_iter().enumerate();
+        levels.map(move |(level, tex)| (idx, level + 1, tex))
+    });
+    mips.par_bridge().try_for_each(|(idx, level, tex)| {
+        let name = format!("atlas_{idx}_mip_{level}.{}", fmt.extension());
+        write_atlas(&dir.join(name), tex)
+    })
+}
+
 fn write_atlas(path: &Path, tex: Texture) -> Result<()> {
     let img = img::from_texture(tex)?;
     img::save(path, img)
@@ -88,6 +112,12 @@ fn write_sprites(sprites: Vec<DicedSprite>, dir: &Path) -> Result<()> {
     fs::write(path, json).map_err(Error::Io)
 }
 
+fn write_stats(stats: &Stats, dir: &Path) -> Result<()> {
+    let json = json::stats_to_json(stats);
+    let path = dir.join("stats.json");
+    fs::write(path, json).map_err(Error::Io)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,9 +1,9 @@
 //! Command line interface of the library.
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use cli::models::*;
 use indicatif::{ProgressBar, ProgressStyle};
-use sprite_dicing::{Pivot, Prefs};
+use sprite_dicing::{AlphaCrop, Coordinates, Pivot, Prefs, UnitOrder, UvOrigin, Winding, YAxis};
 use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
@@ -28,12 +28,33 @@ struct Args {
     /// The size of border between adjacent diced units, in pixels.
     #[arg(short, long, default_value_t = 2)]
     pad: u32,
+    /// Size of the GPU compression blocks to align the atlas cells to, in pixels.
+    #[arg(long, default_value_t = 1)]
+    block: u32,
+    /// Grow the padding to fill the extra space in the aligned atlas cells.
+    #[arg(long, default_value_t = false)]
+    grow_pad: bool,
+    /// Number of mip levels to generate for the atlas textures.
+    #[arg(long, default_value_t = 0)]
+    mips: u32,
     /// Relative inset (in 0.0-1.0 range) of the diced units UV coordinates.
     #[arg(short, long, default_value_t = 0.0)]
     inset: f32,
     /// Trim transparent areas on the built meshes.
     #[arg(short, long, default_value_t = false)]
     trim: bool,
+    /// Crop the mesh quads of the diced units to their opaque pixels.
+    #[arg(long, value_enum, default_value_t = Crop::Off)]
+    crop: Crop,
+    /// Generate outlines of the sprites with the specified max deviation, in pixels.
+    #[arg(long, value_name = "TOLERANCE")]
+    outline: Option<f32>,
+    /// Merge units which are tinted copies of other units, carrying the tint in vertex colors.
+    #[arg(long, default_value_t = false)]
+    tints: bool,
+    /// Emit sprites identical to an earlier sprite as aliases referencing its mesh.
+    #[arg(long, default_value_t = false)]
+    aliases: bool,
     /// Maximum size of a single generated atlas texture.
     #[arg(short, long, default_value_t = 2048)]
     limit: u32,
@@ -43,12 +64,59 @@ struct Args {
     /// Force atlas size to always be power of two.
     #[arg(long, default_value_t = false)]
     pot: bool,
+    /// Order in which the diced units are laid out inside atlas textures.
+    #[arg(long, value_enum, default_value_t = Order::Hash)]
+    order: Order,
+    /// Print statistics of the operation and write them to the out directory.
+    #[arg(long, default_value_t = false)]
+    stats: bool,
     /// Pixel per unit ratio of the diced sprite mesh vertices.
     #[arg(long, default_value_t = 100.0)]
     ppu: f32,
     /// Origin of the diced sprite mesh, in relative offsets from top-left corner.
     #[arg(long, num_args = 2, default_values_t = [0.5, 0.5])]
     pivot: Vec<f32>,
+    /// Direction of the Y-axis of the diced sprite mesh vertices, rect and pivot.
+    #[arg(long, value_enum, default_value_t = Axis::Down)]
+    y_axis: Axis,
+    /// Corner of the atlas texture where the UV coordinates originate.
+    #[arg(long, value_enum, default_value_t = Origin::TopLeft)]
+    uv_origin: Origin,
+    /// Order of the mesh triangle vertices, as seen when the sprite is displayed upright.
+    #[arg(long, value_enum, default_value_t = Wind::Ccw)]
+    winding: Wind,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum Order {
+    Hash,
+    Source,
+    Color,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum Crop {
+    Off,
+    Rect,
+    Polygon,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum Axis {
+    Down,
+    Up,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum Origin {
+    TopLeft,
+    BottomLeft,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum Wind {
+    Ccw,
+    Cw,
 }
 
 fn main() -> Result<()> {
@@ -64,20 +132,58 @@ fn main() -> Result<()> {
         separator: args.separator,
         atlas_format: args.format,
     };
+    let bar_cb = bar.clone();
     let prefs = Prefs {
         unit_size: args.size,
         padding: args.pad,
+        block_alignment: args.block,
+        grow_padding: args.grow_pad,
+        mip_levels: args.mips,
         uv_inset: args.inset,
         trim_transparent: args.trim,
+        alpha_crop: match args.crop {
+            Crop::Off => AlphaCrop::Off,
+            Crop::Rect => AlphaCrop::Rect,
+            Crop::Polygon => AlphaCrop::Polygon,
+        },
+        outline_tolerance: args.outline,
+        dedupe_tints: args.tints,
+        alias_duplicates: args.aliases,
         atlas_size_limit: args.limit,
         atlas_square: args.square,
         atlas_pot: args.pot,
+        unit_order: match args.order {
+            Order::Hash => UnitOrder::Hash,
+            Order::Source => UnitOrder::Source,
+            Order::Color => UnitOrder::Color,
+        },
+        collect_stats: args.stats,
         ppu: args.ppu,
         pivot: Pivot::new(args.pivot[0], args.pivot[1]),
+        coordinates: Coordinates {
+            y_axis: match args.y_axis {
+                Axis::Down => YAxis::Down,
+                Axis::Up => YAxis::Up,
+            },
+            uv_origin: match args.uv_origin {
+                Origin::TopLeft => UvOrigin::TopLeft,
+                Origin::BottomLeft => UvOrigin::BottomLeft,
+            },
+            winding: match args.winding {
+                Wind::Ccw => Winding::CounterClockwise,
+                Wind::Cw => Winding::Clockwise,
+            },
+        },
         on_progress: Some(Box::new(move |p| {
-            bar.set_position((p.ratio * 100.0) as u64);
-            bar.set_message(p.activity);
+            bar_cb.set_position((p.ratio * 100.0) as u64);
+            bar_cb.set_message(p.activity);
         })),
+        on_cancel: None,
     };
-    cli::dice_dir(&args.dir, &fs_prefs, &prefs)
+    let stats = cli::dice_dir(&args.dir, &fs_prefs, &prefs)?;
+    bar.finish_and_clear();
+    if let Some(stats) = stats {
+        print!("{}", cli::stats_to_text(&stats));
+    }
+    Ok(())
 }
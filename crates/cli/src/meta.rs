@@ -0,0 +1,62 @@
+use crate::models::*;
+use sprite_dicing::Metadata;
+use std::{fs, io, path::Path};
+
+/// Extension of the files with sprite metadata, stored next to the sprite textures.
+const EXTENSION: &str = "metadata";
+
+/// Loads metadata of the sprite with specified texture path from the adjacent file with
+/// the same name and `.metadata` extension, when exists; returns empty metadata otherwise.
+pub fn load(texture_path: &Path) -> Result<Metadata> {
+    let path = texture_path.with_extension(EXTENSION);
+    if !path.is_file() {
+        return Ok(Metadata::new());
+    }
+    parse(&fs::read_to_string(&path)?).map_err(|line| {
+        let msg = format!(
+            "Invalid line {line} in '{}'; expected 'key=value'.",
+            path.display()
+        );
+        Error::Io(io::Error::new(io::ErrorKind::InvalidData, msg))
+    })
+}
+
+/// Parses `key=value` pairs specified on separate lines; empty lines and lines starting
+/// with `#` are ignored. Returns number of the first malformed line on failure.
+fn parse(text: &str) -> std::result::Result<Metadata, usize> {
+    let mut metadata = Metadata::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                metadata.insert(key.trim().to_owned(), value.trim().to_owned());
+            }
+            _ => return Err(idx + 1),
+        }
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_key_value_pairs() {
+        let metadata = parse("# comment\n\ntag = hero\nborder=1,2,3,4\nempty=\nurl=a=b\n").unwrap();
+        assert_eq!(metadata.len(), 4);
+        assert_eq!(metadata["tag"], "hero");
+        assert_eq!(metadata["border"], "1,2,3,4");
+        assert_eq!(metadata["empty"], "");
+        assert_eq!(metadata["url"], "a=b");
+    }
+
+    #[test]
+    fn errs_on_malformed_lines() {
+        assert_eq!(parse("tag=hero\nfoo\n"), Err(2));
+        assert_eq!(parse("=value"), Err(1));
+    }
+}
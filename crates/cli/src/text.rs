@@ -0,0 +1,68 @@
+use sprite_dicing::Stats;
+
+/// Formats specified dicing statistics into human-readable summary.
+pub fn stats_to_text(stats: &Stats) -> String {
+    let sprites = stats.sprites.len();
+    let units = stats.sprites.iter().map(|s| s.units).sum::<usize>();
+    let unique = stats.atlases.iter().map(|a| a.units).sum::<usize>();
+    let shared = stats.sprites.iter().filter(|s| s.shared > 0).count();
+    let mut text = format!(
+        "Diced {sprites} sprites into {units} units ({unique} unique), \
+        {shared} sprites share units with others.\n"
+    );
+    for (idx, atlas) in stats.atlases.iter().enumerate() {
+        text += &format!(
+            "Atlas {idx}: {}x{}, {} units, {:.1}% occupied, {} texels wasted.\n",
+            atlas.size.width,
+            atlas.size.height,
+            atlas.units,
+            atlas.occupancy * 100.0,
+            atlas.wasted_texels
+        );
+    }
+    text += &format!(
+        "{} KB / ({} KB + {} KB) = {:.2} compression ratio.\n",
+        stats.source_bytes / 1024,
+        stats.atlas_bytes / 1024,
+        stats.mesh_bytes / 1024,
+        stats.compression_ratio()
+    );
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sprite_dicing::{AtlasStats, SpriteStats, USize};
+
+    #[test]
+    fn formats_stats_summary() {
+        let sprite = |id: &str, shared| SpriteStats {
+            id: id.to_owned(),
+            units: 4,
+            unique: 3,
+            shared,
+            vertices: 16,
+            indices: 24,
+            mesh_bytes: 1024,
+        };
+        let stats = Stats {
+            sprites: vec![sprite("a", 1), sprite("b", 0)],
+            atlases: vec![AtlasStats {
+                size: USize::new(64, 32),
+                units: 5,
+                occupancy: 0.625,
+                wasted_texels: 768,
+            }],
+            source_bytes: 10240,
+            atlas_bytes: 8192,
+            mesh_bytes: 2048,
+        };
+        assert_eq!(
+            stats_to_text(&stats),
+            "Diced 2 sprites into 8 units (5 unique), 1 sprites share units with others.\n\
+            Atlas 0: 64x32, 5 units, 62.5% occupied, 768 texels wasted.\n\
+            10 KB / (8 KB + 2 KB) = 1.00 compression ratio.\n"
+        );
+    }
+}
@@ -0,0 +1,453 @@
+use crate::models::*;
+use crate::optimizer::clamp_paddings;
+use crate::packer::downsample;
+use crate::verifier::Projection;
+
+/// Measures seams of the diced sprites and searches for the padding and inset removing them.
+pub(crate) fn analyze(
+    sources: &[SourceSprite],
+    mut prefs: Prefs,
+    check: &BleedCheck,
+) -> Result<Bleed> {
+    if check.scales.is_empty() || check.mip_levels.is_empty() {
+        return Err(Error::EmptyBleedCheck);
+    }
+    if let Some(scale) = check.scales.iter().find(|s| !(s.is_finite() && **s > 0.0)) {
+        return Err(Error::InvalidBleedScale { scale: *scale });
+    }
+    if !(check.uv_inset_step.is_finite() && check.uv_inset_step > 0.0) {
+        return Err(Error::InvalidInsetStep {
+            step: check.uv_inset_step,
+        });
+    }
+
+    // Seams are measured along the edges of the whole untinted unit quads.
+    prefs.alpha_crop = AlphaCrop::Off;
+    prefs.dedupe_tints = false;
+    let on_progress = prefs.on_progress.take();
+    report(&on_progress, 0.0, "Measuring seams...");
+    let seams = measure(sources, &prefs, check)?;
+
+    let (padding, uv_inset) = (prefs.padding, prefs.uv_inset);
+    let mut rec_padding = None;
+    for pad in 0..=check.max_padding.min(prefs.unit_size) {
+        let ratio = (pad + 1) as f32 / (check.max_padding + 2) as f32 / 2.0;
+        report(&on_progress, ratio, &format!("Evaluating padding {pad}..."));
+        prefs.padding = pad;
+        if is_seamless(&clamp_paddings(sources, pad), &prefs, check)? {
+            rec_padding = Some(pad);
+            break;
+        }
+    }
+
+    prefs.padding = padding;
+    let mut rec_inset = None;
+    let steps = (check.max_uv_inset.min(0.5) / check.uv_inset_step).floor() as u32;
+    for step in 0..=steps {
+        let inset = step as f32 * check.uv_inset_step;
+        let ratio = 0.5 + step as f32 / (steps + 1) as f32 / 2.0;
+        report(
+            &on_progress,
+            ratio,
+            &format!("Evaluating UV inset {inset}..."),
+        );
+        prefs.uv_inset = inset;
+        if is_seamless(sources, &prefs, check)? {
+            rec_inset = Some(inset);
+            break;
+        }
+    }
+    prefs.uv_inset = uv_inset;
+
+    Ok(Bleed {
+        seams,
+        padding: rec_padding,
+        uv_inset: rec_inset,
+    })
+}
+
+fn report(cb: &Option<ProgressCallback>, ratio: f32, activity: &str) {
+    if let Some(cb) = cb {
+        let activity = activity.to_owned();
+        cb(Progress { ratio, activity });
+    }
+}
+
+fn is_seamless(sources: &[SourceSprite], prefs: &Prefs, check: &BleedCheck) -> Result<bool> {
+    Ok(measure(sources, prefs, check)?
+        .iter()
+        .all(|s| s.pixels == 0))
+}
+
+fn measure(sources: &[SourceSprite], prefs: &Prefs, check: &BleedCheck) -> Result<Vec<Seam>> {
+    let arts = crate::dice(sources, prefs)?;
+    let max_level = check.mip_levels.iter().copied().max().unwrap_or(0);
+    let atlases = arts
+        .atlases
+        .iter()
+        .zip(arts.mips.iter())
+        .map(|(atlas, mips)| build_chain(atlas, mips, max_level))
+        .collect::<Vec<_>>();
+    let mut seams = Vec::with_capacity(arts.sprites.len());
+    for sprite in arts.sprites.iter() {
+        let source = sources.iter().find(|s| s.id == sprite.id).unwrap();
+        let quads = map_quads(arts.resolve(sprite), prefs);
+        let coverage = map_coverage(&quads, &source.texture);
+        let ctx = Context {
+            tolerance: check.tolerance,
+            atlas: &atlases[sprite.atlas_index],
+            source: build_chain(&source.texture, &[], max_level),
+            quads,
+            coverage,
+        };
+        let mut seam = Seam {
+            id: sprite.id.to_owned(),
+            max_error: 0.0,
+            pixels: 0,
+        };
+        for &scale in check.scales.iter() {
+            for &level in check.mip_levels.iter() {
+                measure_at(&ctx, scale, level as usize, &mut seam);
+            }
+        }
+        seams.push(seam);
+    }
+    Ok(seams)
+}
+
+struct Context<'a> {
+    tolerance: f32,
+    /// Mip chain of the atlas texture, starting with the atlas itself.
+    atlas: &'a [Texture],
+    /// Mip chain of the source texture, starting with the source texture itself.
+    source: Vec<Texture>,
+    /// Quads of the diced sprite mesh, in order of the units.
+    quads: Vec<Quad>,
+    /// Index of the quad covering each pixel of the source texture, in row-major order; the
+    /// units don't have to be aligned to the unit size grid (eg, when snapped to 9-slice lines).
+    coverage: Vec<Option<usize>>,
+}
+
+/// Quad of the diced sprite mesh projected to the source texture space.
+struct Quad {
+    min: (f32, f32),
+    max: (f32, f32),
+    uv_min: Uv,
+    uv_max: Uv,
+}
+
+fn build_chain(texture: &Texture, mips: &[Texture], max_level: u32) -> Vec<Texture> {
+    let mut chain = Vec::with_capacity(max_level as usize + 1);
+    chain.push(texture.to_owned());
+    chain.extend(mips.iter().take(max_level as usize).cloned());
+    while chain.len() <= max_level as usize {
+        let last = chain.last().unwrap();
+        if last.width < 2 || last.height < 2 {
+            break;
+        }
+        chain.push(downsample(last));
+    }
+    chain
+}
+
+fn map_quads(sprite: &DicedSprite, prefs: &Prefs) -> Vec<Quad> {
+    let sprite = &crate::builder::convert(sprite.to_owned(), &prefs.coordinates);
+    let proj = Projection::new(sprite);
+    let mut quads = Vec::with_capacity(sprite.vertices.len() / 4);
+    // Vertices layout by index:
+    // min -> [0] [3]
+    //        [1] [2] <- max
+    for idx in (0..sprite.vertices.len()).step_by(4) {
+        let min = proj.project(&sprite.vertices[idx]);
+        let max = proj.project(&sprite.vertices[idx + 2]);
+        let uv_min = sprite.uvs[idx].to_owned();
+        let uv_max = sprite.uvs[idx + 2].to_owned();
+        quads.push(Quad {
+            min,
+            max,
+            uv_min,
+            uv_max,
+        });
+    }
+    quads
+}
+
+fn map_coverage(quads: &[Quad], source: &Texture) -> Vec<Option<usize>> {
+    let mut coverage = vec![None; (source.width * source.height) as usize];
+    for (idx, quad) in quads.iter().enumerate() {
+        let (min_x, min_y) = (quad.min.0.round() as u32, quad.min.1.round() as u32);
+        let max_x = (quad.max.0.round() as u32).min(source.width);
+        let max_y = (quad.max.1.round() as u32).min(source.height);
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                coverage[(x + y * source.width) as usize] = Some(idx);
+            }
+        }
+    }
+    coverage
+}
+
+fn measure_at(ctx: &Context, scale: f32, level: usize, seam: &mut Seam) {
+    let (Some(atlas), Some(source)) = (ctx.atlas.get(level), ctx.source.get(level)) else {
+        return;
+    };
+    let size = (ctx.source[0].width as f32, ctx.source[0].height as f32);
+    // Bilinear filter of the mip level reaches texels this far from the sampled point.
+    let reach = (2 << level) as f32;
+    let width = (size.0 * scale).ceil() as u32;
+    let height = (size.1 * scale).ceil() as u32;
+    for y in 0..height {
+        for x in 0..width {
+            let px = (x as f32 + 0.5) / scale;
+            let py = (y as f32 + 0.5) / scale;
+            if px >= size.0 || py >= size.1 {
+                continue;
+            }
+            let pixel = px as usize + py as usize * ctx.source[0].width as usize;
+            let Some(quad) = ctx.coverage[pixel].map(|idx| &ctx.quads[idx]) else {
+                continue;
+            };
+            if !is_near_edge(quad, px, py, reach) {
+                continue;
+            }
+            let u = lerp(quad.uv_min.u, quad.uv_max.u, quad.min.0, quad.max.0, px);
+            let v = lerp(quad.uv_min.v, quad.uv_max.v, quad.min.1, quad.max.1, py);
+            let rendered = sample(atlas, u, v);
+            let expected = sample(source, px / size.0, py / size.1);
+            let error = diff(rendered, expected);
+            seam.max_error = seam.max_error.max(error);
+            if error > ctx.tolerance {
+                seam.pixels += 1;
+            }
+        }
+    }
+}
+
+fn is_near_edge(quad: &Quad, x: f32, y: f32, reach: f32) -> bool {
+    x - quad.min.0 < reach
+        || quad.max.0 - x < reach
+        || y - quad.min.1 < reach
+        || quad.max.1 - y < reach
+}
+
+fn lerp(from: f32, to: f32, min: f32, max: f32, pos: f32) -> f32 {
+    from + (to - from) * (pos - min) / (max - min)
+}
+
+/// Samples specified texture at specified UV with bilinear filtering, clamping to edges.
+fn sample(tex: &Texture, u: f32, v: f32) -> [f32; 4] {
+    let x = u * tex.width as f32 - 0.5;
+    let y = v * tex.height as f32 - 0.5;
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let texel = |dx: i32, dy: i32| {
+        let tx = (x0 as i32 + dx).clamp(0, tex.width as i32 - 1) as u32;
+        let ty = (y0 as i32 + dy).clamp(0, tex.height as i32 - 1) as u32;
+        tex.pixels[(tx + ty * tex.width) as usize].to_raw()
+    };
+    let (a, b, c, d) = (texel(0, 0), texel(1, 0), texel(0, 1), texel(1, 1));
+    let mut result = [0.0; 4];
+    for i in 0..4 {
+        let top = a[i] as f32 + (b[i] as f32 - a[i] as f32) * fx;
+        let bottom = c[i] as f32 + (d[i] as f32 - c[i] as f32) * fx;
+        result[i] = top + (bottom - top) * fy;
+    }
+    result
+}
+
+fn diff(a: [f32; 4], b: [f32; 4]) -> f32 {
+    // Color of the fully transparent pixels is not visible and doesn't matter.
+    if a[3] == 0.0 && b[3] == 0.0 {
+        return 0.0;
+    }
+    (0..4).map(|i| (a[i] - b[i]).abs()).fold(0.0, f32::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::*;
+    use crate::models::*;
+
+    #[test]
+    fn errs_when_no_scales() {
+        let check = BleedCheck {
+            scales: vec![],
+            ..BleedCheck::default()
+        };
+        assert!(analyze(vec![&RGB4X4], defaults(), &check)
+            .is_err_and(|e| e == Error::EmptyBleedCheck
+                && e.to_string() == "Specify at least one scale and mip level to analyze."));
+    }
+
+    #[test]
+    fn errs_when_scale_not_positive() {
+        let check = BleedCheck {
+            scales: vec![1.0, 0.0],
+            ..BleedCheck::default()
+        };
+        assert!(analyze(vec![&RGB4X4], defaults(), &check)
+            .is_err_and(|e| e == Error::InvalidBleedScale { scale: 0.0 }));
+        for scale in [f32::NAN, f32::INFINITY] {
+            let check = scales(vec![scale]);
+            assert!(analyze(vec![&RGB4X4], defaults(), &check)
+                .is_err_and(|e| matches!(e, Error::InvalidBleedScale { .. })));
+        }
+    }
+
+    #[test]
+    fn errs_when_inset_step_not_positive() {
+        let check = BleedCheck {
+            uv_inset_step: 0.0,
+            ..BleedCheck::default()
+        };
+        assert!(analyze(vec![&RGB4X4], defaults(), &check)
+            .is_err_and(|e| e == Error::InvalidInsetStep { step: 0.0 }));
+        let check = BleedCheck {
+            uv_inset_step: f32::NAN,
+            ..BleedCheck::default()
+        };
+        assert!(analyze(vec![&RGB4X4], defaults(), &check)
+            .is_err_and(|e| matches!(e, Error::InvalidInsetStep { .. })));
+    }
+
+    #[test]
+    fn no_seams_at_native_scale() {
+        let bleed = analyze(vec![&PLT4X4], defaults(), &scales(vec![1.0])).unwrap();
+        assert_eq!(bleed.seams[0].pixels, 0);
+        assert_eq!(bleed.seams[0].max_error, 0.0);
+        assert_eq!(bleed.padding, Some(0));
+        assert_eq!(bleed.uv_inset, Some(0.0));
+    }
+
+    #[test]
+    fn no_seams_at_native_scale_when_units_snapped_to_border() {
+        // Units are snapped to the slice lines, so they are not aligned to the unit size grid.
+        let prefs = Prefs {
+            unit_size: 2,
+            ..defaults()
+        };
+        let sprites = [SourceSprite {
+            border: Some(Border::new(1, 1, 1, 0)),
+            ..PLT4X4.sprite()
+        }];
+        let bleed = crate::analyze_bleed(&sprites, prefs, &scales(vec![1.0])).unwrap();
+        assert_eq!(bleed.seams[0].pixels, 0);
+    }
+
+    #[test]
+    fn seams_at_fractional_scale_without_padding() {
+        let bleed = analyze(vec![&PLT4X4], defaults(), &scales(vec![1.5])).unwrap();
+        assert!(bleed.seams[0].pixels > 0);
+        assert!(bleed.seams[0].max_error > 0.0);
+    }
+
+    #[test]
+    fn recommends_padding() {
+        let bleed = analyze(vec![&PLT4X4], defaults(), &scales(vec![1.5])).unwrap();
+        assert_eq!(bleed.padding, Some(1));
+    }
+
+    #[test]
+    fn recommends_padding_with_sprite_padding_overrides() {
+        let prefs = Prefs {
+            padding: 1,
+            ..defaults()
+        };
+        let sprites = [SourceSprite {
+            padding: Some(1),
+            ..PLT4X4.sprite()
+        }];
+        let bleed = crate::analyze_bleed(&sprites, prefs, &scales(vec![1.5])).unwrap();
+        assert_eq!(bleed.padding, Some(1));
+    }
+
+    #[test]
+    fn recommends_larger_padding_for_lower_mips() {
+        // Texels of mip 2 cover 4 atlas texels, so cells have to be aligned to them.
+        let prefs = || Prefs {
+            unit_size: 4,
+            ..defaults()
+        };
+        let (red, blue) = (solid(R), solid(B));
+        let bleed = analyze(vec![&red, &blue], prefs(), &scales(vec![1.5])).unwrap();
+        assert_eq!(bleed.padding, Some(1));
+        let check = BleedCheck {
+            mip_levels: vec![2],
+            ..scales(vec![1.5])
+        };
+        let bleed = analyze(vec![&red, &blue], prefs(), &check).unwrap();
+        assert_eq!(bleed.padding, Some(2));
+    }
+
+    #[test]
+    fn recommends_inset() {
+        // Units of solid color are not distorted by the inset.
+        let prefs = Prefs {
+            unit_size: 4,
+            ..defaults()
+        };
+        let check = BleedCheck {
+            max_uv_inset: 0.5,
+            uv_inset_step: 0.05,
+            ..scales(vec![1.5])
+        };
+        let bleed = analyze(vec![&solid(R), &solid(B)], prefs, &check).unwrap();
+        let inset = bleed.uv_inset.unwrap();
+        assert!(inset > 0.0 && inset <= 0.25);
+    }
+
+    #[test]
+    fn doesnt_recommend_when_seams_cant_be_removed() {
+        // Shared units are padded with neighbours of the first occurrence; padding
+        // doesn't help, while required inset distorts the content.
+        let check = BleedCheck {
+            max_uv_inset: 0.5,
+            ..scales(vec![1.5])
+        };
+        let bleed = analyze(vec![&RGB4X4], defaults(), &check).unwrap();
+        assert_eq!(bleed.padding, None);
+        assert_eq!(bleed.uv_inset, None);
+    }
+
+    #[test]
+    fn reports_progress() {
+        let progress = sample_progress(|p| {
+            let sprites = [M1X1.sprite()];
+            drop(crate::analyze_bleed(&sprites, p, &BleedCheck::default()))
+        });
+        assert!(progress.activity.starts_with("Evaluating UV inset"));
+    }
+
+    fn analyze(src: Vec<&dyn AnySource>, prefs: Prefs, check: &BleedCheck) -> Result<Bleed> {
+        let mut sprites = src.iter().map(|s| s.sprite()).collect::<Vec<_>>();
+        for (idx, sprite) in sprites.iter_mut().enumerate() {
+            sprite.id = idx.to_string();
+        }
+        crate::analyze_bleed(&sprites, prefs, check)
+    }
+
+    fn solid(color: Pixel) -> Texture {
+        Texture {
+            width: 4,
+            height: 4,
+            pixels: vec![color; 16],
+        }
+    }
+
+    fn scales(scales: Vec<f32>) -> BleedCheck {
+        BleedCheck {
+            scales,
+            ..BleedCheck::default()
+        }
+    }
+
+    fn defaults() -> Prefs {
+        Prefs {
+            unit_size: 1,
+            padding: 0,
+            trim_transparent: false,
+            ppu: 1.0,
+            ..Prefs::default()
+        }
+    }
+}
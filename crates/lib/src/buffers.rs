@@ -0,0 +1,211 @@
+use crate::models::*;
+
+/// Packs the diced sprite mesh into interleaved vertex and compact index buffers.
+pub(crate) fn pack(sprite: &DicedSprite, uv_format: UvFormat) -> MeshBuffers {
+    let has_colors = !sprite.colors.is_empty();
+    let color_size = if has_colors { 4 } else { 0 };
+    let stride = 2 * size_of::<f32>() + 2 * uv_format.size() + color_size;
+    let mut vertices = Vec::with_capacity(sprite.vertices.len() * stride);
+    for (idx, (vertex, uv)) in sprite.vertices.iter().zip(&sprite.uvs).enumerate() {
+        vertices.extend(vertex.x.to_le_bytes());
+        vertices.extend(vertex.y.to_le_bytes());
+        write_uv(&mut vertices, uv.u, uv_format);
+        write_uv(&mut vertices, uv.v, uv_format);
+        if has_colors {
+            vertices.extend(sprite.colors[idx].to_raw());
+        }
+    }
+
+    let index_format = if sprite.vertices.len() <= u16::MAX as usize + 1 {
+        IndexFormat::Uint16
+    } else {
+        IndexFormat::Uint32
+    };
+    let mut indices = Vec::with_capacity(sprite.indices.len() * index_format.size());
+    for &index in sprite.indices.iter() {
+        match index_format {
+            IndexFormat::Uint16 => indices.extend((index as u16).to_le_bytes()),
+            IndexFormat::Uint32 => indices.extend((index as u32).to_le_bytes()),
+        }
+    }
+
+    MeshBuffers {
+        stride,
+        uv_format,
+        has_colors,
+        index_format,
+        vertices,
+        indices,
+    }
+}
+
+fn write_uv(buffer: &mut Vec<u8>, value: f32, format: UvFormat) {
+    match format {
+        UvFormat::Float32 => buffer.extend(value.to_le_bytes()),
+        UvFormat::Float16 => buffer.extend(to_f16(value).to_le_bytes()),
+        UvFormat::Unorm16 => buffer.extend(to_unorm16(value).to_le_bytes()),
+    }
+}
+
+/// Converts the value to IEEE 754 half-precision float bits, rounding to nearest.
+fn to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+    let mantissa = bits & 0x7f_ffff;
+    if value.is_nan() {
+        return sign | 0x7e00;
+    }
+    if exp >= 31 {
+        return sign | 0x7c00;
+    }
+    if exp <= 0 {
+        // Subnormal half; values below half of the smallest subnormal are flushed to zero.
+        if exp < -10 {
+            return sign;
+        }
+        let shift = (14 - exp) as u32;
+        let mantissa = mantissa | 0x80_0000;
+        return sign | ((mantissa + (1 << (shift - 1))) >> shift) as u16;
+    }
+    // Ties are rounded to even; carry of the rounding overflows into the exponent,
+    // which is the correct result.
+    let half = sign | ((exp as u16) << 10) | (mantissa >> 13) as u16;
+    let round = mantissa & 0x1000 != 0;
+    let sticky = mantissa & 0xfff != 0;
+    half + (round && (sticky || half & 1 != 0)) as u16
+}
+
+fn to_unorm16(value: f32) -> u16 {
+    (value.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn vertices_are_interleaved_with_uvs() {
+        let sprite = dice(&RGBY);
+        let buffers = pack(&sprite, UvFormat::Float32);
+        assert_eq!(buffers.stride, 16);
+        assert_eq!(buffers.vertex_count(), sprite.vertices.len());
+        for (idx, chunk) in buffers.vertex_bytes().chunks_exact(16).enumerate() {
+            let floats =
+                [0, 4, 8, 12].map(|o| f32::from_le_bytes(chunk[o..o + 4].try_into().unwrap()));
+            assert_eq!(floats[0], sprite.vertices[idx].x);
+            assert_eq!(floats[1], sprite.vertices[idx].y);
+            assert_eq!(floats[2], sprite.uvs[idx].u);
+            assert_eq!(floats[3], sprite.uvs[idx].v);
+        }
+    }
+
+    #[test]
+    fn colors_are_interleaved_when_specified() {
+        let mut sprite = dice(&R1X1);
+        assert!(!pack(&sprite, UvFormat::Float32).has_colors);
+        let tint = Pixel::new(10, 20, 30, 40);
+        sprite.colors = vec![tint; 4];
+        let buffers = pack(&sprite, UvFormat::Float16);
+        assert!(buffers.has_colors);
+        assert_eq!(buffers.stride, 16);
+        assert_eq!(buffers.vertex_count(), 4);
+        for chunk in buffers.vertex_bytes().chunks_exact(16) {
+            assert_eq!(chunk[12..16], [10, 20, 30, 40]);
+        }
+    }
+
+    #[test]
+    fn small_meshes_use_16_bit_indices() {
+        let sprite = dice(&RGBY);
+        let buffers = pack(&sprite, UvFormat::Float32);
+        assert_eq!(buffers.index_format, IndexFormat::Uint16);
+        assert_eq!(buffers.index_count(), sprite.indices.len());
+        let indices = buffers
+            .index_bytes()
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]) as usize)
+            .collect::<Vec<_>>();
+        assert_eq!(indices, sprite.indices);
+    }
+
+    #[test]
+    fn large_meshes_use_32_bit_indices() {
+        let mut sprite = dice(&R1X1);
+        sprite.vertices = vec![Vertex::new(0.0, 0.0); 65537];
+        sprite.uvs = vec![Uv::new(0.0, 0.0); 65537];
+        sprite.indices = vec![0, 65535, 65536];
+        let buffers = pack(&sprite, UvFormat::Float32);
+        assert_eq!(buffers.index_format, IndexFormat::Uint32);
+        assert_eq!(buffers.index_count(), 3);
+        assert_eq!(&buffers.index_bytes()[8..], &65536u32.to_le_bytes());
+    }
+
+    #[test]
+    fn half_float_uvs_are_packed() {
+        let sprite = dice(&R1X1);
+        let buffers = pack(&sprite, UvFormat::Float16);
+        assert_eq!(buffers.stride, 12);
+        assert_eq!(buffers.vertex_count(), 4);
+        for (idx, chunk) in buffers.vertex_bytes().chunks_exact(12).enumerate() {
+            assert_eq!(chunk[8..10], to_f16(sprite.uvs[idx].u).to_le_bytes());
+            assert_eq!(chunk[10..12], to_f16(sprite.uvs[idx].v).to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn normalized_uvs_are_packed() {
+        let mut sprite = dice(&R1X1);
+        sprite.uvs = vec![
+            Uv::new(0.0, 1.0),
+            Uv::new(0.5, 2.0),
+            Uv::new(-1.0, 0.0),
+            Uv::new(1.0, 1.0),
+        ];
+        let buffers = pack(&sprite, UvFormat::Unorm16);
+        let uv = |idx: usize| {
+            let bytes = &buffers.vertex_bytes()[idx * 12 + 8..idx * 12 + 12];
+            (
+                u16::from_le_bytes([bytes[0], bytes[1]]),
+                u16::from_le_bytes([bytes[2], bytes[3]]),
+            )
+        };
+        assert_eq!(uv(0), (0, 65535));
+        assert_eq!(uv(1), (32768, 65535));
+        assert_eq!(uv(2), (0, 0));
+    }
+
+    #[test]
+    fn converts_to_half_float() {
+        assert_eq!(to_f16(0.0), 0x0000);
+        assert_eq!(to_f16(-0.0), 0x8000);
+        assert_eq!(to_f16(1.0), 0x3c00);
+        assert_eq!(to_f16(0.5), 0x3800);
+        assert_eq!(to_f16(0.25), 0x3400);
+        assert_eq!(to_f16(-2.0), 0xc000);
+        assert_eq!(to_f16(65504.0), 0x7bff);
+        assert_eq!(to_f16(1e6), 0x7c00);
+        assert_eq!(to_f16(f32::NAN) & 0x7e00, 0x7e00);
+        // Smallest subnormal and rounding to nearest even.
+        assert_eq!(to_f16(2f32.powi(-24)), 0x0001);
+        assert_eq!(to_f16(2f32.powi(-14)), 0x0400);
+        assert_eq!(to_f16(1.0 + 2f32.powi(-12)), 0x3c00);
+        assert_eq!(to_f16(1.0 + 2f32.powi(-11)), 0x3c00);
+        assert_eq!(to_f16(1.0 + 2f32.powi(-11) + 2f32.powi(-12)), 0x3c01);
+        assert_eq!(to_f16(1.0 + 3.0 * 2f32.powi(-11)), 0x3c02);
+    }
+
+    fn dice(src: &dyn AnySource) -> DicedSprite {
+        let prefs = Prefs {
+            unit_size: 1,
+            padding: 0,
+            ..Prefs::default()
+        };
+        crate::dice(&[src.sprite()], &prefs)
+            .unwrap()
+            .sprites
+            .pop()
+            .unwrap()
+    }
+}
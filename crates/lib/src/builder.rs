@@ -1,20 +1,27 @@
 use crate::models::*;
 use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 /// Builds data required to reconstruct diced sprites at runtime: mesh, uvs, etc.
 pub(crate) fn build(packed: &[Atlas], prefs: &Prefs) -> Result<Vec<DicedSprite>> {
     if prefs.ppu <= 0.0 {
-        return Err(Error::Spec("PPU can't be zero or negative."));
+        return Err(Error::InvalidPpu { ppu: prefs.ppu });
     }
 
     let total = packed.iter().map(|a| a.packed.len()).sum();
     let mut sprites = Vec::with_capacity(total);
+    let mut originals = HashMap::<u64, Vec<usize>>::new();
 
     for (atlas_idx, atlas) in packed.iter().enumerate() {
         for diced_tex in atlas.packed.iter() {
-            Progress::report(prefs, 3, sprites.len(), total, "Building diced sprites");
-            let ctx = new_ctx(atlas, atlas_idx, diced_tex, prefs);
-            sprites.push(build_it(ctx));
+            Progress::report(prefs, 3, sprites.len(), total, "Building diced sprites")?;
+            let ctx = new_ctx(packed, atlas_idx, diced_tex, prefs);
+            let mut sprite = convert(build_it(ctx), &prefs.coordinates);
+            if prefs.alias_duplicates {
+                let candidates = originals.entry(fingerprint(diced_tex)).or_default();
+                alias(&mut sprite, &sprites, candidates);
+            }
+            sprites.push(sprite);
         }
     }
 
@@ -23,44 +30,98 @@ pub(crate) fn build(packed: &[Atlas], prefs: &Prefs) -> Result<Vec<DicedSprite>>
 
 struct Context<'a> {
     ppu: f32,
+    unit_size: u32,
     trim: bool,
+    tints: bool,
+    y_axis: YAxis,
     default_pivot: &'a Pivot,
     atlas_idx: usize,
     diced: &'a DicedTexture,
-    uv_rects: &'a HashMap<u64, FRect>,
+    atlases: &'a [Atlas],
     vertices: Vec<Vertex>,
     uvs: Vec<Uv>,
+    /// Indices of the opaque units' faces.
     indices: Vec<usize>,
+    /// Indices of the translucent units' faces, appended after the opaque ones when built.
+    translucent: Vec<usize>,
+    slices: Vec<Slice>,
+    outlines: Vec<Vec<Vertex>>,
+    colors: Vec<Pixel>,
+    atlas_indices: Vec<usize>,
 }
 
 fn new_ctx<'a>(
-    atlas: &'a Atlas,
+    atlases: &'a [Atlas],
     atlas_idx: usize,
     diced: &'a DicedTexture,
     prefs: &'a Prefs,
 ) -> Context<'a> {
     Context {
-        ppu: prefs.ppu,
-        trim: prefs.trim_transparent,
+        ppu: diced.ppu.unwrap_or(prefs.ppu),
+        unit_size: prefs.unit_size,
+        trim: diced.trim_transparent.unwrap_or(prefs.trim_transparent),
+        tints: prefs.dedupe_tints,
+        y_axis: prefs.coordinates.y_axis,
         default_pivot: &prefs.pivot,
         atlas_idx,
         diced,
-        uv_rects: &atlas.rects,
+        atlases,
         vertices: vec![],
         uvs: vec![],
         indices: vec![],
+        translucent: vec![],
+        slices: vec![],
+        outlines: vec![],
+        colors: vec![],
+        atlas_indices: vec![],
     }
 }
 
 fn build_it(mut ctx: Context) -> DicedSprite {
     for unit in ctx.diced.units.iter() {
-        let uv_rect = &ctx.uv_rects[&unit.hash];
-        build_unit(&mut ctx, &unit.rect, uv_rect);
+        // The units found on the base atlases are sampled from there (see dice_with_base).
+        let atlas_idx = ctx.diced.base.get(&unit.hash).copied();
+        let atlas_idx = atlas_idx.unwrap_or(ctx.atlas_idx);
+        let uv_rect = &ctx.atlases[atlas_idx].rects[&unit.hash];
+        let first = ctx.vertices.len();
+        build_unit(&mut ctx, unit, uv_rect);
+        let count = ctx.vertices.len() - first;
+        if !ctx.diced.base.is_empty() {
+            ctx.atlas_indices
+                .extend(std::iter::repeat_n(atlas_idx, count));
+        }
+        if let Some(border) = &ctx.diced.border {
+            let slice = eval_slice(&unit.rect, border, &ctx.diced.size);
+            ctx.slices.extend(std::iter::repeat_n(slice, count));
+        }
+        if ctx.tints {
+            ctx.colors.extend(std::iter::repeat_n(unit.tint, count));
+        }
+    }
+    for outline in ctx.diced.outlines.iter() {
+        let outline = outline.iter().map(|(x, y)| Vertex {
+            x: *x as f32 / ctx.ppu,
+            y: *y as f32 / ctx.ppu,
+        });
+        ctx.outlines.push(outline.collect());
     }
 
+    // Pivots are specified in the requested coordinates, while the mesh is built Y-down.
     let pivot = ctx.diced.pivot.as_ref().unwrap_or(ctx.default_pivot);
+    let pivot = &match ctx.y_axis {
+        YAxis::Down => pivot.to_owned(),
+        YAxis::Up => Pivot::new(pivot.x, 1.0 - pivot.y),
+    };
     let rect = eval_rect(&ctx, pivot);
     offset_vertices(&mut ctx, &rect, pivot);
+    let pixel_rect = eval_pixel_rect(&ctx);
+    let pixel_pivot = Pivot::new(
+        pivot.x * pixel_rect.width as f32,
+        pivot.y * pixel_rect.height as f32,
+    );
+    let opaque_indices = 0..ctx.indices.len();
+    ctx.indices.append(&mut ctx.translucent);
+    let translucent_indices = opaque_indices.end..ctx.indices.len();
 
     DicedSprite {
         id: ctx.diced.id.to_owned(),
@@ -68,53 +129,192 @@ fn build_it(mut ctx: Context) -> DicedSprite {
         vertices: ctx.vertices,
         uvs: ctx.uvs,
         indices: ctx.indices,
+        opaque_indices,
+        translucent_indices,
         rect,
         pivot: pivot.to_owned(),
+        source_size: ctx.diced.size.to_owned(),
+        pixel_rect,
+        pixel_pivot,
+        metadata: ctx.diced.metadata.to_owned(),
+        border: ctx.diced.border,
+        slices: ctx.slices,
+        outlines: ctx.outlines,
+        colors: ctx.colors,
+        atlas_indices: ctx.atlas_indices,
+        alias_of: None,
+    }
+}
+
+/// Hashes the diced content of the texture and the overrides affecting its mesh; textures
+/// with equal fingerprints are expected to produce identical meshes when packed together.
+pub(crate) fn fingerprint(diced: &DicedTexture) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (diced.size.width, diced.size.height).hash(&mut hasher);
+    let pivot = diced.pivot.as_ref().map(|p| (p.x.to_bits(), p.y.to_bits()));
+    (pivot, diced.ppu.map(f32::to_bits), diced.trim_transparent).hash(&mut hasher);
+    let border = diced.border.map(|b| (b.left, b.right, b.top, b.bottom));
+    let bounds = diced.bounds.as_ref().map(|b| (b.x, b.y, b.width, b.height));
+    (border, bounds).hash(&mut hasher);
+    diced.outlines.hash(&mut hasher);
+    let mut base = diced.base.iter().collect::<Vec<_>>();
+    base.sort_unstable();
+    base.hash(&mut hasher);
+    for unit in diced.units.iter() {
+        let (rect, crop) = (&unit.rect, &unit.crop.rect);
+        (unit.hash, rect.x, rect.y, rect.width, rect.height).hash(&mut hasher);
+        (crop.x, crop.y, crop.width, crop.height, unit.crop.cuts).hash(&mut hasher);
+        (unit.opaque, unit.tint).hash(&mut hasher);
     }
+    hasher.finish()
+}
+
+/// Turns the sprite into an alias of the earlier built candidate with identical mesh, if any;
+/// otherwise registers the sprite (to be pushed next) as a candidate for the later sprites.
+fn alias(sprite: &mut DicedSprite, built: &[DicedSprite], candidates: &mut Vec<usize>) {
+    let mut originals = candidates.iter().map(|idx| &built[*idx]);
+    let Some(original) = originals.find(|o| is_same_mesh(o, sprite)) else {
+        candidates.push(built.len());
+        return;
+    };
+    sprite.alias_of = Some(original.id.to_owned());
+    sprite.vertices = vec![];
+    sprite.uvs = vec![];
+    sprite.indices = vec![];
+    sprite.slices = vec![];
+    sprite.outlines = vec![];
+    sprite.colors = vec![];
+    sprite.atlas_indices = vec![];
 }
 
-fn build_unit(ctx: &mut Context, unit_rect: &URect, uv_rect: &FRect) {
-    let unit_rect = scale_unit_rect(ctx, unit_rect);
-    build_quad(ctx, &unit_rect, uv_rect);
+/// Whether the sprites have identical meshes on the same atlases.
+pub(crate) fn is_same_mesh(a: &DicedSprite, b: &DicedSprite) -> bool {
+    a.atlas_index == b.atlas_index
+        && a.vertices == b.vertices
+        && a.uvs == b.uvs
+        && a.indices == b.indices
+        && a.opaque_indices == b.opaque_indices
+        && a.slices == b.slices
+        && a.outlines == b.outlines
+        && a.colors == b.colors
+        && a.atlas_indices == b.atlas_indices
 }
 
-fn scale_unit_rect(ctx: &Context, unit_rect: &URect) -> FRect {
-    FRect {
-        x: unit_rect.x as f32 / ctx.ppu,
-        y: unit_rect.y as f32 / ctx.ppu,
-        width: unit_rect.width as f32 / ctx.ppu,
-        height: unit_rect.height as f32 / ctx.ppu,
+/// Converts the diced sprite between the default and specified coordinate conventions;
+/// converting twice restores the original sprite.
+pub(crate) fn convert(mut sprite: DicedSprite, coords: &Coordinates) -> DicedSprite {
+    if coords.y_axis == YAxis::Up {
+        let outlines = sprite.outlines.iter_mut().flatten();
+        for vertex in sprite.vertices.iter_mut().chain(outlines) {
+            vertex.y = -vertex.y;
+        }
+        sprite.rect.y = -(sprite.rect.y + sprite.rect.height);
+        sprite.pivot.y = 1.0 - sprite.pivot.y;
+        let rect = &mut sprite.pixel_rect;
+        rect.y = sprite.source_size.height - rect.y - rect.height;
+        sprite.pixel_pivot.y = rect.height as f32 - sprite.pixel_pivot.y;
+    }
+    if coords.uv_origin == UvOrigin::BottomLeft {
+        for uv in sprite.uvs.iter_mut() {
+            uv.v = 1.0 - uv.v;
+        }
+    }
+    if coords.winding == Winding::Clockwise {
+        for face in sprite.indices.chunks_exact_mut(3) {
+            face.swap(1, 2);
+        }
+        for outline in sprite.outlines.iter_mut() {
+            outline.reverse();
+        }
     }
+    sprite
 }
 
-fn build_quad(ctx: &mut Context, unit_rect: &FRect, uv_rect: &FRect) {
+fn build_unit(ctx: &mut Context, unit: &DicedUnit, uv_rect: &FRect) {
     let i = ctx.vertices.len();
+    let outline = eval_outline(&unit.crop);
+
+    for (x, y) in outline.iter() {
+        ctx.vertices.push(Vertex {
+            x: (unit.rect.x + x) as f32 / ctx.ppu,
+            y: (unit.rect.y + y) as f32 / ctx.ppu,
+        });
+        ctx.uvs.push(Uv {
+            u: uv_rect.x + uv_rect.width * (*x as f32 / ctx.unit_size as f32),
+            v: uv_rect.y + uv_rect.height * (*y as f32 / ctx.unit_size as f32),
+        });
+    }
 
-    let x_min = unit_rect.x;
-    let y_min = unit_rect.y;
-    let x_max = unit_rect.x + unit_rect.width;
-    let y_max = unit_rect.y + unit_rect.height;
-
-    let u_min = uv_rect.x;
-    let v_min = uv_rect.y;
-    let u_max = uv_rect.x + uv_rect.width;
-    let v_max = uv_rect.y + uv_rect.height;
-
-    ctx.vertices.extend([
-        Vertex { x: x_min, y: y_min },
-        Vertex { x: x_min, y: y_max },
-        Vertex { x: x_max, y: y_max },
-        Vertex { x: x_max, y: y_min },
-    ]);
-
-    ctx.uvs.extend([
-        Uv { u: u_min, v: v_min },
-        Uv { u: u_min, v: v_max },
-        Uv { u: u_max, v: v_max },
-        Uv { u: u_max, v: v_min },
-    ]);
-
-    ctx.indices.extend([i, i + 1, i + 2, i + 2, i + 3, i]);
+    // Triangle fan of the convex outline; yields (0 1 2) (2 3 0) for a quad.
+    let indices = match unit.opaque {
+        true => &mut ctx.indices,
+        false => &mut ctx.translucent,
+    };
+    indices.extend([i, i + 1, i + 2]);
+    for n in 2..outline.len() - 1 {
+        indices.extend([i + n, i + n + 1, i]);
+    }
+}
+
+/// Evaluates vertices outlining the covered area of a unit, in pixels relative to the unit
+/// rect. Starts at the top-left corner and goes counter-clockwise (as seen on screen):
+/// min -> [0] [3]
+///        [1] [2] <- max
+/// Cut corners are replaced with a pair of vertices on the adjacent sides.
+fn eval_outline(crop: &Crop) -> Vec<(u32, u32)> {
+    let URect {
+        x: x0,
+        y: y0,
+        width,
+        height,
+    } = crop.rect;
+    let (x1, y1) = (x0 + width, y0 + height);
+    let [tl, bl, br, tr] = crop.cuts;
+    let mut outline = Vec::with_capacity(crop.vertices());
+    match tl {
+        0 => outline.push((x0, y0)),
+        c => outline.extend([(x0 + c, y0), (x0, y0 + c)]),
+    }
+    match bl {
+        0 => outline.push((x0, y1)),
+        c => outline.extend([(x0, y1 - c), (x0 + c, y1)]),
+    }
+    match br {
+        0 => outline.push((x1, y1)),
+        c => outline.extend([(x1 - c, y1), (x1, y1 - c)]),
+    }
+    match tr {
+        0 => outline.push((x1, y0)),
+        c => outline.extend([(x1, y0 + c), (x1 - c, y0)]),
+    }
+    outline
+}
+
+/// Resolves 9-slice region of the unit; the units never cross the slice lines.
+fn eval_slice(unit_rect: &URect, border: &Border, size: &USize) -> Slice {
+    let column = eval_band(unit_rect.x, border.left, size.width - border.right);
+    let row = eval_band(unit_rect.y, border.top, size.height - border.bottom);
+    match (row, column) {
+        (0, 0) => Slice::TopLeft,
+        (0, 1) => Slice::Top,
+        (0, _) => Slice::TopRight,
+        (1, 0) => Slice::Left,
+        (1, 1) => Slice::Center,
+        (1, _) => Slice::Right,
+        (_, 0) => Slice::BottomLeft,
+        (_, 1) => Slice::Bottom,
+        (_, _) => Slice::BottomRight,
+    }
+}
+
+fn eval_band(pos: u32, head: u32, tail_start: u32) -> u8 {
+    if pos < head {
+        0
+    } else if pos < tail_start {
+        1
+    } else {
+        2
+    }
 }
 
 fn eval_rect(ctx: &Context, pivot: &Pivot) -> Rect {
@@ -126,21 +326,29 @@ fn eval_rect(ctx: &Context, pivot: &Pivot) -> Rect {
 }
 
 fn eval_fit_rect(ctx: &Context) -> Rect {
-    let mut min_x = f32::INFINITY;
-    let mut min_y = f32::INFINITY;
-    let mut max_x = f32::NEG_INFINITY;
-    let mut max_y = f32::NEG_INFINITY;
+    // Bounded by the units rather than the vertices, which may be cropped to opaque pixels.
+    let rect = eval_pixel_rect(ctx);
+    let min_x = rect.x as f32 / ctx.ppu;
+    let min_y = rect.y as f32 / ctx.ppu;
+    let max_x = (rect.x + rect.width) as f32 / ctx.ppu;
+    let max_y = (rect.y + rect.height) as f32 / ctx.ppu;
+    Rect::new(min_x, min_y, max_x - min_x, max_y - min_y)
+}
 
-    for vertex in ctx.vertices.iter() {
-        min_x = min_x.min(vertex.x);
-        min_y = min_y.min(vertex.y);
-        max_x = max_x.max(vertex.x);
-        max_y = max_y.max(vertex.y);
+fn eval_pixel_rect(ctx: &Context) -> URect {
+    let size = &ctx.diced.size;
+    if !ctx.trim {
+        return URect::new(0, 0, size.width, size.height);
     }
-
-    let width = (max_x - min_x).abs();
-    let height = (max_y - min_y).abs();
-    Rect::new(min_x, min_y, width, height)
+    if let Some(bounds) = &ctx.diced.bounds {
+        return bounds.to_owned();
+    }
+    let rects = ctx.diced.units.iter().map(|u| &u.rect);
+    let min_x = rects.clone().map(|r| r.x).min().unwrap_or(0);
+    let min_y = rects.clone().map(|r| r.y).min().unwrap_or(0);
+    let max_x = rects.clone().map(|r| r.x + r.width).max().unwrap_or(0);
+    let max_y = rects.map(|r| r.y + r.height).max().unwrap_or(0);
+    URect::new(min_x, min_y, max_x - min_x, max_y - min_y)
 }
 
 fn eval_full_rect(ctx: &Context, pivot: &Pivot) -> Rect {
@@ -160,9 +368,10 @@ fn offset_vertices(ctx: &mut Context, rect: &Rect, pivot: &Pivot) {
         offset_y += rect.y;
     }
 
-    for idx in 0..ctx.vertices.len() {
-        ctx.vertices[idx].x -= offset_x;
-        ctx.vertices[idx].y -= offset_y;
+    let outlines = ctx.outlines.iter_mut().flatten();
+    for vertex in ctx.vertices.iter_mut().chain(outlines) {
+        vertex.x -= offset_x;
+        vertex.y -= offset_y;
     }
 }
 
@@ -177,7 +386,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "PPU can't be zero or negative.")]
+    #[should_panic(expected = "InvalidPpu { ppu: 0.0 }")]
     fn errs_when_ppu_zero() {
         let prefs = Prefs {
             ppu: 0.0,
@@ -357,6 +566,27 @@ mod tests {
         assert_eq!(sprites[0].pivot, Pivot::new(0.5, 0.5));
     }
 
+    #[test]
+    fn per_sprite_ppu_and_trim_override_defaults() {
+        let mut sprite = TTTM.sprite();
+        sprite.ppu = Some(2.0);
+        sprite.trim_transparent = Some(true);
+        let sources = [
+            sprite,
+            SourceSprite {
+                id: "default".to_owned(),
+                ..TTTM.sprite()
+            },
+        ];
+        let sprites = crate::dice(&sources, &defaults()).unwrap().sprites;
+        let overridden = sprites.iter().find(|s| s.id == "TEST").unwrap();
+        let default = sprites.iter().find(|s| s.id == "default").unwrap();
+        assert_eq!(overridden.rect, Rect::new(0.5, 0.5, 0.5, 0.5));
+        assert_eq!(overridden.vertices[2], Vertex::new(0.5, 0.5));
+        assert_eq!(default.rect, Rect::new(0.0, 0.0, 2.0, 2.0));
+        assert_eq!(default.vertices[2], Vertex::new(2.0, 2.0));
+    }
+
     #[test]
     fn sprite_rect_size_equals_source_texture_divided_by_ppu() {
         let prefs = Prefs {
@@ -473,6 +703,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn overflow_uvs_are_cropped() {
+        let prefs = Prefs {
+            unit_size: 2,
+            padding: 1,
+            ..defaults()
+        };
+        let sprite = &build(vec![&M1X1], &prefs)[0];
+        assert_eq!(sprite.uvs[0], Uv::new(0.25, 0.25));
+        assert_eq!(sprite.uvs[2], Uv::new(0.5, 0.5));
+    }
+
     #[test]
     fn transparent_sprites_are_ignored() {
         let prefs = Prefs {
@@ -482,6 +724,284 @@ mod tests {
         assert!(&build(vec![&TTTT], &prefs).is_empty());
     }
 
+    #[test]
+    fn sprite_exposes_source_size_and_pixel_rect() {
+        let sprite = &build(vec![&TTMT], &defaults())[0];
+        assert_eq!(sprite.source_size, USize::new(2, 2));
+        assert_eq!(sprite.pixel_rect, URect::new(0, 0, 2, 2));
+        let prefs = Prefs {
+            trim_transparent: true,
+            ..defaults()
+        };
+        let sprite = &build(vec![&TTMT], &prefs)[0];
+        assert_eq!(sprite.source_size, USize::new(2, 2));
+        assert_eq!(sprite.pixel_rect, URect::new(0, 1, 1, 1));
+    }
+
+    #[test]
+    fn pixel_rect_includes_whole_units() {
+        let prefs = Prefs {
+            unit_size: 2,
+            trim_transparent: true,
+            ..defaults()
+        };
+        let sprite = &build(vec![&RGB3X1], &prefs)[0];
+        assert_eq!(sprite.pixel_rect, URect::new(0, 0, 3, 1));
+    }
+
+    #[test]
+    fn pixel_pivot_is_relative_pivot_scaled_by_pixel_rect() {
+        let prefs = Prefs {
+            ppu: 100.0,
+            trim_transparent: true,
+            ..defaults()
+        };
+        let sprite = &build(vec![&(&BTGT, (0.5, 0.25))], &prefs)[0];
+        assert_eq!(sprite.pixel_rect, URect::new(0, 0, 1, 2));
+        assert_eq!(sprite.pixel_pivot, Pivot::new(0.5, 0.5));
+    }
+
+    #[test]
+    fn when_y_up_pixel_rect_and_pivot_are_flipped() {
+        let prefs = Prefs {
+            trim_transparent: true,
+            coordinates: coords(YAxis::Up, UvOrigin::TopLeft, Winding::CounterClockwise),
+            ..defaults()
+        };
+        let sprite = &build(vec![&(&MTTT, (0.0, 0.25))], &prefs)[0];
+        assert_eq!(sprite.pixel_rect, URect::new(0, 1, 1, 1));
+        assert_eq!(sprite.pixel_pivot, Pivot::new(0.0, 0.25));
+    }
+
+    #[test]
+    fn when_y_up_vertices_rect_and_pivot_are_flipped() {
+        let prefs = Prefs {
+            coordinates: coords(YAxis::Up, UvOrigin::TopLeft, Winding::CounterClockwise),
+            ..defaults()
+        };
+        let sprite = &build(vec![&(&RGB1X3, (0.0, 0.0))], &prefs)[0];
+        assert_eq!(sprite.rect, Rect::new(0.0, 0.0, 1.0, 3.0));
+        assert_eq!(sprite.pivot, Pivot::new(0.0, 0.0));
+        let top = sprite.vertices.iter().map(|v| v.y).fold(f32::MIN, f32::max);
+        assert_eq!(top, 3.0);
+        let sprite = &build(vec![&(&RGB1X3, (0.5, 0.0))], &prefs)[0];
+        assert_eq!(sprite.rect, Rect::new(-0.5, 0.0, 1.0, 3.0));
+        assert_eq!(sprite.pivot, Pivot::new(0.5, 0.0));
+    }
+
+    #[test]
+    fn when_y_up_trimmed_rect_is_flipped() {
+        let prefs = Prefs {
+            trim_transparent: true,
+            coordinates: coords(YAxis::Up, UvOrigin::TopLeft, Winding::CounterClockwise),
+            ..defaults()
+        };
+        assert_eq!(
+            build(vec![&TTMT], &prefs)[0].rect,
+            Rect::new(0.0, -2.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn when_uv_origin_bottom_left_v_is_flipped() {
+        let prefs = Prefs {
+            unit_size: 2,
+            padding: 1,
+            coordinates: coords(YAxis::Down, UvOrigin::BottomLeft, Winding::CounterClockwise),
+            ..defaults()
+        };
+        let sprite = &build(vec![&M1X1], &prefs)[0];
+        assert_eq!(sprite.uvs[0], Uv::new(0.25, 0.75));
+        assert_eq!(sprite.uvs[2], Uv::new(0.5, 0.5));
+    }
+
+    #[test]
+    fn when_clockwise_triangles_are_reversed() {
+        let ccw = &build(vec![&B1X1], &defaults())[0];
+        assert_eq!(ccw.indices, vec![0, 1, 2, 2, 3, 0]);
+        let prefs = Prefs {
+            coordinates: coords(YAxis::Down, UvOrigin::TopLeft, Winding::Clockwise),
+            ..defaults()
+        };
+        let cw = &build(vec![&B1X1], &prefs)[0];
+        assert_eq!(cw.indices, vec![0, 2, 1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn winding_is_preserved_when_y_is_flipped() {
+        // Signed area of the first triangle is negative for counter-clockwise faces
+        // on screen when Y is down and positive when Y is up.
+        let area = |sprite: &DicedSprite| {
+            let [a, b, c] = [0, 1, 2].map(|i| &sprite.vertices[sprite.indices[i]]);
+            (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+        };
+        let down = &build(vec![&B1X1], &defaults())[0];
+        let prefs = Prefs {
+            coordinates: coords(YAxis::Up, UvOrigin::TopLeft, Winding::CounterClockwise),
+            ..defaults()
+        };
+        let up = &build(vec![&B1X1], &prefs)[0];
+        assert!(area(down) < 0.0);
+        assert!(area(up) > 0.0);
+    }
+
+    #[test]
+    fn vertices_are_tagged_with_slices() {
+        let mut sprite = RGB4X4.sprite();
+        sprite.border = Some(Border::new(1, 1, 1, 1));
+        let prefs = Prefs {
+            unit_size: 2,
+            ..defaults()
+        };
+        let diced = &crate::dice(&[sprite], &prefs).unwrap().sprites[0];
+        assert_eq!(diced.border, Some(Border::new(1, 1, 1, 1)));
+        assert_eq!(diced.vertices.len(), diced.slices.len());
+        let quad = |slice| {
+            let idx = diced.slices.iter().position(|s| *s == slice).unwrap();
+            assert!(diced.slices[idx..idx + 4].iter().all(|s| *s == slice));
+            diced.vertices[idx..idx + 4].to_vec()
+        };
+        assert_eq!(quad(Slice::TopLeft)[0], Vertex::new(0.0, 0.0));
+        assert_eq!(quad(Slice::Center)[0], Vertex::new(1.0, 1.0));
+        assert_eq!(quad(Slice::Center)[2], Vertex::new(3.0, 3.0));
+        assert_eq!(quad(Slice::BottomRight)[2], Vertex::new(4.0, 4.0));
+        assert_eq!(quad(Slice::Right)[0], Vertex::new(3.0, 1.0));
+    }
+
+    #[test]
+    fn when_cropped_to_rect_quad_covers_opaque_pixels() {
+        let prefs = Prefs {
+            unit_size: 2,
+            padding: 1,
+            alpha_crop: AlphaCrop::Rect,
+            ..defaults()
+        };
+        let sprite = &build(vec![&TTTM], &prefs)[0];
+        assert_eq!(sprite.vertices[0], Vertex::new(1.0, 1.0));
+        assert_eq!(sprite.vertices[2], Vertex::new(2.0, 2.0));
+        assert_eq!(sprite.uvs[0], Uv::new(0.5, 0.5));
+        assert_eq!(sprite.uvs[2], Uv::new(0.75, 0.75));
+        assert_eq!(sprite.indices, vec![0, 1, 2, 2, 3, 0]);
+    }
+
+    #[test]
+    fn when_cropped_to_polygon_transparent_corners_are_cut() {
+        #[rustfmt::skip]
+        let diagonal = Texture { width: 4, height: 4, pixels: vec![
+            R, T, T, T,
+            T, R, T, T,
+            T, T, R, T,
+            T, T, T, R,
+        ]};
+        let prefs = Prefs {
+            unit_size: 4,
+            alpha_crop: AlphaCrop::Polygon,
+            ..defaults()
+        };
+        let sprite = &build(vec![&diagonal], &prefs)[0];
+        assert_eq!(
+            sprite.vertices,
+            vec![
+                Vertex::new(0.0, 0.0),
+                Vertex::new(0.0, 1.0),
+                Vertex::new(3.0, 4.0),
+                Vertex::new(4.0, 4.0),
+                Vertex::new(4.0, 3.0),
+                Vertex::new(1.0, 0.0),
+            ]
+        );
+        assert_eq!(sprite.uvs[2], Uv::new(0.75, 1.0));
+        assert_eq!(sprite.indices, vec![0, 1, 2, 2, 3, 0, 3, 4, 0, 4, 5, 0]);
+    }
+
+    #[test]
+    fn cropped_sprites_are_reconstructed() {
+        let sources = vec![RGB4X4.sprite(), BTGT.sprite(), TTMT.sprite(), BGRT.sprite()];
+        let sources = sources
+            .into_iter()
+            .enumerate()
+            .map(|(idx, s)| SourceSprite {
+                id: idx.to_string(),
+                ..s
+            })
+            .collect::<Vec<_>>();
+        for alpha_crop in [AlphaCrop::Rect, AlphaCrop::Polygon] {
+            let prefs = Prefs {
+                unit_size: 2,
+                alpha_crop,
+                ..defaults()
+            };
+            let arts = crate::dice(&sources, &prefs).unwrap();
+            assert!(crate::verify(&sources, &arts, &prefs).is_empty());
+        }
+    }
+
+    #[test]
+    fn opaque_faces_precede_translucent_ones() {
+        let half = Pixel::new(255, 0, 0, 128);
+        let tex = Texture {
+            width: 2,
+            height: 1,
+            pixels: vec![half, R],
+        };
+        let sprite = &build(vec![&tex], &defaults())[0];
+        assert_eq!(sprite.indices, vec![4, 5, 6, 6, 7, 4, 0, 1, 2, 2, 3, 0]);
+        assert_eq!(sprite.opaque_indices, 0..6);
+        assert_eq!(sprite.translucent_indices, 6..12);
+        let opaque = &build(vec![&RGB4X4], &defaults())[0];
+        assert_eq!(opaque.opaque_indices, 0..opaque.indices.len());
+        assert!(opaque.translucent_indices.is_empty());
+    }
+
+    #[test]
+    fn slices_are_empty_when_border_not_specified() {
+        assert!(build(vec![&RGB4X4], &defaults())[0].slices.is_empty());
+    }
+
+    #[test]
+    fn outlines_are_in_vertices_space() {
+        assert!(build(vec![&BGRT], &defaults())[0].outlines.is_empty());
+        let prefs = Prefs {
+            outline_tolerance: Some(0.0),
+            pivot: Pivot::new(0.5, 0.5),
+            ppu: 2.0,
+            ..defaults()
+        };
+        let sprite = &build(vec![&BGRT], &prefs)[0];
+        let outline = &sprite.outlines[0];
+        assert_eq!(outline.len(), 6);
+        assert_eq!(outline[0], sprite.vertices[0]);
+        assert_eq!(outline[0], Vertex::new(-0.5, -0.5));
+        assert_eq!(outline[2], Vertex::new(0.0, 0.5));
+    }
+
+    #[test]
+    fn outlines_follow_coordinates() {
+        let prefs = |coordinates| Prefs {
+            outline_tolerance: Some(0.0),
+            coordinates,
+            ..defaults()
+        };
+        let down = &build(vec![&R1X1], &prefs(Coordinates::default()))[0];
+        assert_eq!(down.outlines[0], down.vertices);
+        let up_cw = coords(YAxis::Up, UvOrigin::TopLeft, Winding::Clockwise);
+        let up = &build(vec![&R1X1], &prefs(up_cw))[0];
+        let reversed = up.vertices.iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(up.outlines[0], reversed);
+    }
+
+    #[test]
+    fn metadata_is_carried_to_diced_sprite() {
+        let mut sprites = vec![R1X1.sprite(), B1X1.sprite()];
+        sprites[0].id = "red".to_owned();
+        sprites[0].metadata = Metadata::from([("tag".to_owned(), "hero".to_owned())]);
+        let arts = crate::dice(&sprites, &defaults()).unwrap();
+        let red = arts.sprites.iter().find(|s| s.id == "red").unwrap();
+        let blue = arts.sprites.iter().find(|s| s.id != "red").unwrap();
+        assert_eq!(red.metadata, sprites[0].metadata);
+        assert!(blue.metadata.is_empty());
+    }
+
     #[test]
     fn reports_progress() {
         let progress = sample_progress(|p| drop(build(vec![&BTGT], &p)));
@@ -519,13 +1039,94 @@ mod tests {
         }
     }
 
+    #[test]
+    fn sequence_frames_share_bounds_and_pivot() {
+        let prefs = Prefs {
+            trim_transparent: true,
+            ..defaults()
+        };
+        let mut sprites = vec![TTTM.sprite(), MTTT.sprite()];
+        sprites[0].pivot = Some(Pivot::new(1.0, 1.0));
+        for (idx, sprite) in sprites.iter_mut().enumerate() {
+            sprite.id = idx.to_string();
+            sprite.sequence = Some("seq".to_owned());
+        }
+        let built = build_sprites(sprites, &prefs);
+        for sprite in built.iter() {
+            assert_eq!(sprite.pixel_rect, URect::new(0, 0, 2, 2));
+            assert_eq!(sprite.rect, built[0].rect);
+            assert_eq!(sprite.pivot, Pivot::new(1.0, 1.0));
+        }
+        let frame = |id| Quad::from_1x1(built.iter().find(|s| s.id == id).unwrap());
+        assert_eq!(frame("0").top_left, Vertex::new(-1.0, -1.0));
+        assert_eq!(frame("1").top_left, Vertex::new(-2.0, -2.0));
+    }
+
+    #[test]
+    fn identical_sprites_are_aliased() {
+        let prefs = Prefs {
+            alias_duplicates: true,
+            ..defaults()
+        };
+        let sprites = build_sprites(duplicates(None), &prefs);
+        let original = sprites.iter().find(|s| s.alias_of.is_none()).unwrap();
+        let alias = sprites.iter().find(|s| s.alias_of.is_some()).unwrap();
+        assert_eq!(alias.alias_of.as_ref(), Some(&original.id));
+        assert_ne!(alias.id, original.id);
+        assert!(alias.vertices.is_empty() && alias.uvs.is_empty() && alias.indices.is_empty());
+        assert_eq!(original.vertices.len(), 16 * 4);
+        assert_eq!(alias.atlas_index, original.atlas_index);
+        assert_eq!(alias.rect, original.rect);
+        assert_eq!(alias.opaque_indices, original.opaque_indices);
+    }
+
+    #[test]
+    fn sprites_with_different_mesh_are_not_aliased() {
+        let prefs = Prefs {
+            alias_duplicates: true,
+            ..defaults()
+        };
+        let pivot = Some(Pivot::new(0.5, 0.5));
+        let sprites = build_sprites(duplicates(pivot), &prefs);
+        assert!(sprites.iter().all(|s| s.alias_of.is_none()));
+    }
+
+    #[test]
+    fn sprites_are_not_aliased_when_disabled() {
+        let sprites = build_sprites(duplicates(None), &defaults());
+        assert!(sprites.iter().all(|s| s.alias_of.is_none()));
+        assert_eq!(sprites[0].vertices, sprites[1].vertices);
+    }
+
+    /// Copy of the RGB4X4 sprite with the specified pivot, followed by the original.
+    fn duplicates(pivot: Option<Pivot>) -> Vec<SourceSprite> {
+        let copy = SourceSprite {
+            id: "copy".to_owned(),
+            pivot,
+            ..RGB4X4.sprite()
+        };
+        vec![copy, RGB4X4.sprite()]
+    }
+
     fn build(src: Vec<&dyn AnySource>, prefs: &Prefs) -> Vec<DicedSprite> {
         let sprites = src.into_iter().map(|s| s.sprite()).collect::<Vec<_>>();
+        build_sprites(sprites, prefs)
+    }
+
+    fn build_sprites(sprites: Vec<SourceSprite>, prefs: &Prefs) -> Vec<DicedSprite> {
         let diced = crate::dicer::dice(&sprites, prefs).unwrap();
         let packed = crate::packer::pack(diced, prefs).unwrap();
         crate::builder::build(&packed, prefs).unwrap()
     }
 
+    fn coords(y_axis: YAxis, uv_origin: UvOrigin, winding: Winding) -> Coordinates {
+        Coordinates {
+            y_axis,
+            uv_origin,
+            winding,
+        }
+    }
+
     fn defaults() -> Prefs {
         Prefs {
             ppu: 1.0,
@@ -1,32 +1,57 @@
 use crate::models::*;
+use crate::outliner;
+use crate::tinter;
 use std::cmp;
-use std::collections::HashSet;
-use std::hash::{DefaultHasher, Hash, Hasher};
+use std::collections::{HashMap, HashSet};
 
 /// Chops source sprite textures and collects unique units.
 pub(crate) fn dice(sprites: &[SourceSprite], prefs: &Prefs) -> Result<Vec<DicedTexture>> {
     if prefs.unit_size == 0 {
-        return Err(Error::Spec("Unit size can't be zero."));
+        return Err(Error::InvalidUnitSize {
+            unit_size: prefs.unit_size,
+            atlas_size_limit: prefs.atlas_size_limit,
+        });
     }
     if prefs.padding > prefs.unit_size {
-        return Err(Error::Spec("Padding can't be above unit size."));
+        return Err(Error::PaddingTooLarge {
+            padding: prefs.padding,
+            unit_size: prefs.unit_size,
+        });
+    }
+    if prefs.mip_levels > 0
+        && prefs
+            .atlas_size_limit
+            .checked_shr(prefs.mip_levels)
+            .unwrap_or(0)
+            == 0
+    {
+        return Err(Error::TooManyMipLevels {
+            mip_levels: prefs.mip_levels,
+            atlas_size_limit: prefs.atlas_size_limit,
+        });
     }
 
     let mut textures = vec![];
     for (idx, sprite) in sprites.iter().enumerate() {
-        Progress::report(prefs, 1, idx, sprites.len(), "Dicing source textures");
+        Progress::report(prefs, 1, idx, sprites.len(), "Dicing source textures")?;
         let ctx = new_ctx(sprite, prefs);
         if let Some(texture) = dice_it(&ctx) {
             textures.push(texture);
         }
     }
+    unify_sequences(&mut textures, sprites);
+    if prefs.dedupe_tints {
+        tinter::dedupe(&mut textures, prefs);
+    }
 
     Ok(textures)
 }
 
 struct Context<'a> {
     size: u32,
-    pad: u32,
+    cell: Cell,
+    crop: AlphaCrop,
+    outline_tolerance: Option<f32>,
     /// Currently diced source sprite.
     sprite: &'a SourceSprite,
 }
@@ -34,19 +59,23 @@ struct Context<'a> {
 fn new_ctx<'a>(sprite: &'a SourceSprite, prefs: &Prefs) -> Context<'a> {
     Context {
         size: prefs.unit_size,
-        pad: prefs.padding,
+        cell: Cell::new(prefs),
+        crop: prefs.alpha_crop,
+        outline_tolerance: prefs.outline_tolerance,
         sprite,
     }
 }
 
 fn dice_it(ctx: &Context) -> Option<DicedTexture> {
+    let tex = &ctx.sprite.texture;
+    let border = ctx.sprite.border.unwrap_or_default();
+    let columns = split(tex.width, border.left, border.right, ctx.size);
+    let rows = split(tex.height, border.top, border.bottom, ctx.size);
     let mut units = Vec::new();
-    let unit_count_x = ctx.sprite.texture.width.div_ceil(ctx.size);
-    let unit_count_y = ctx.sprite.texture.height.div_ceil(ctx.size);
 
-    for x in 0..unit_count_x {
-        for y in 0..unit_count_y {
-            if let Some(unit) = dice_at(x, y, ctx) {
+    for column in columns.iter() {
+        for row in rows.iter() {
+            if let Some(unit) = dice_at(column, row, ctx) {
                 units.push(unit);
             }
         }
@@ -58,34 +87,199 @@ fn dice_it(ctx: &Context) -> Option<DicedTexture> {
 
     Some(DicedTexture {
         id: ctx.sprite.id.to_owned(),
-        size: USize::new(ctx.sprite.texture.width, ctx.sprite.texture.height),
+        size: USize::new(tex.width, tex.height),
         unique: units.iter().map(|u| u.hash).collect::<HashSet<_>>(),
         pivot: ctx.sprite.pivot.to_owned(),
+        metadata: ctx.sprite.metadata.to_owned(),
+        border: ctx.sprite.border,
+        ppu: ctx.sprite.ppu,
+        trim_transparent: ctx.sprite.trim_transparent,
+        bounds: None,
+        outlines: match ctx.outline_tolerance {
+            Some(tolerance) => outliner::trace(tex, tolerance),
+            None => vec![],
+        },
         units,
+        base: HashMap::new(),
     })
 }
 
-fn dice_at(unit_x: u32, unit_y: u32, ctx: &Context) -> Option<DicedUnit> {
+/// Assigns the frames of each sequence the union of their unit rects and the pivot of the first
+/// frame (in order of the source sprites), so that the trimmed frames stay aligned.
+fn unify_sequences(textures: &mut [DicedTexture], sprites: &[SourceSprite]) {
+    let mut pivots = HashMap::<&str, &Option<Pivot>>::new();
+    let mut frames = HashMap::<&str, &str>::new();
+    for sprite in sprites.iter() {
+        if let Some(sequence) = &sprite.sequence {
+            pivots.entry(sequence).or_insert(&sprite.pivot);
+            frames.insert(&sprite.id, sequence);
+        }
+    }
+    if frames.is_empty() {
+        return;
+    }
+    // Min and max corners of the frame units, mapped by sequence.
+    let mut bounds = HashMap::<&str, (u32, u32, u32, u32)>::new();
+    for tex in textures.iter() {
+        let Some(sequence) = frames.get(tex.id.as_str()) else {
+            continue;
+        };
+        for rect in tex.units.iter().map(|u| &u.rect) {
+            let b = bounds.entry(sequence).or_insert((u32::MAX, u32::MAX, 0, 0));
+            *b = (
+                b.0.min(rect.x),
+                b.1.min(rect.y),
+                b.2.max(rect.x + rect.width),
+                b.3.max(rect.y + rect.height),
+            );
+        }
+    }
+    for tex in textures.iter_mut() {
+        if let Some(sequence) = frames.get(tex.id.as_str()) {
+            let (min_x, min_y, max_x, max_y) = bounds[sequence];
+            tex.bounds = Some(URect::new(min_x, min_y, max_x - min_x, max_y - min_y));
+            tex.pivot = pivots[sequence].to_owned();
+        }
+    }
+}
+
+/// Position and length of a unit over a texture axis.
+struct Span {
+    start: u32,
+    len: u32,
+    /// Slice segment (between the texture edges and/or slice lines) containing the unit.
+    segment: (u32, u32),
+}
+
+/// Splits texture axis of the specified length into unit spans, restarting the grid on each
+/// slice line, so that the units never cross the lines; trailing units are cropped.
+fn split(len: u32, head: u32, tail: u32, size: u32) -> Vec<Span> {
+    let lines = [0, head, len.saturating_sub(tail), len];
+    let mut spans = Vec::new();
+    for segment in lines.windows(2).map(|w| (w[0], w[1])) {
+        let mut start = segment.0;
+        while start < segment.1 {
+            let len = cmp::min(size, segment.1 - start);
+            spans.push(Span {
+                start,
+                len,
+                segment,
+            });
+            start += size;
+        }
+    }
+    spans
+}
+
+fn dice_at(column: &Span, row: &Span, ctx: &Context) -> Option<DicedUnit> {
     let unit_rect = IRect {
-        x: unit_x as i32 * ctx.size as i32,
-        y: unit_y as i32 * ctx.size as i32,
+        x: column.start as i32,
+        y: row.start as i32,
         width: ctx.size,
         height: ctx.size,
     };
 
-    let unit_pixels = get_pixels(&unit_rect, &ctx.sprite.texture);
+    // Pixels outside the slice segment are clamped, so that the content doesn't bleed
+    // over the slice lines, which may be stretched apart when rendered.
+    let rect = URect::new(column.start, row.start, column.len, row.len);
+    let bounds = URect {
+        x: column.segment.0,
+        y: row.segment.0,
+        width: column.segment.1 - column.segment.0,
+        height: row.segment.1 - row.segment.0,
+    };
+    let bounds = match ctx.sprite.padding {
+        Some(pad) => limit_bounds(&bounds, &rect, pad),
+        None => bounds,
+    };
+
+    let unit_pixels = get_pixels(&unit_rect, &bounds, &ctx.sprite.texture);
     if unit_pixels.iter().all(|p| p.a() == 0) {
         return None;
     }
 
     let hash = hash(&unit_pixels);
-    let rect = crop_over_borders(&unit_rect, &ctx.sprite.texture);
-    let padded_rect = pad_rect(&unit_rect, ctx.pad);
-    let pixels = get_pixels(&padded_rect, &ctx.sprite.texture);
-    Some(DicedUnit { rect, pixels, hash })
+    let padded_rect = pad_rect(&unit_rect, &ctx.cell);
+    let pixels = get_pixels(&padded_rect, &bounds, &ctx.sprite.texture);
+    let crop = crop(&unit_pixels, &rect, ctx);
+    let opaque = is_opaque(&unit_pixels, &crop.rect, ctx);
+    Some(DicedUnit {
+        rect,
+        pixels,
+        hash,
+        crop,
+        opaque,
+        tint: tinter::WHITE,
+    })
 }
 
-fn get_pixels(rect: &IRect, tex: &Texture) -> Vec<Pixel> {
+fn crop(pixels: &[Pixel], rect: &URect, ctx: &Context) -> Crop {
+    let rect = URect::new(0, 0, rect.width, rect.height);
+    if ctx.crop == AlphaCrop::Off {
+        let cuts = [0; 4];
+        return Crop { rect, cuts };
+    }
+
+    let opaque = |x: u32, y: u32| pixels[(x + y * ctx.size) as usize].a() > 0;
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+    for y in 0..rect.height {
+        for x in 0..rect.width {
+            if opaque(x, y) {
+                min_x = min_x.min(x);
+                min_y = min_y.min(y);
+                max_x = max_x.max(x + 1);
+                max_y = max_y.max(y + 1);
+            }
+        }
+    }
+
+    let rect = URect::new(min_x, min_y, max_x - min_x, max_y - min_y);
+    if ctx.crop == AlphaCrop::Rect {
+        let cuts = [0; 4];
+        return Crop { rect, cuts };
+    }
+
+    // Maps position counted from the specified corner (in order of the mesh vertices).
+    let (last_x, last_y) = (max_x - 1, max_y - 1);
+    let at = |corner: usize, i: u32, j: u32| match corner {
+        0 => (min_x + i, min_y + j),
+        1 => (min_x + i, last_y - j),
+        2 => (last_x - i, last_y - j),
+        _ => (last_x - i, min_y + j),
+    };
+    let cuts = [0, 1, 2, 3].map(|corner| {
+        eval_cut(&rect, |i, j| {
+            let (x, y) = at(corner, i, j);
+            opaque(x, y)
+        })
+    });
+    Crop { rect, cuts }
+}
+
+fn is_opaque(pixels: &[Pixel], rect: &URect, ctx: &Context) -> bool {
+    let rows = rect.y..rect.y + rect.height;
+    let mut covered = rows.flat_map(|y| (rect.x..rect.x + rect.width).map(move |x| (x, y)));
+    covered.all(|(x, y)| pixels[(x + y * ctx.size) as usize].a() == u8::MAX)
+}
+
+/// Evaluates length of the diagonal cut at a corner of the specified opaque bounds, which
+/// shouldn't cross any opaque pixel; the positions are counted from the corner. Each cut adds
+/// a vertex, so the corner is only cut when the cut saves at least 1/16 of the bounds area.
+fn eval_cut(rect: &URect, opaque: impl Fn(u32, u32) -> bool) -> u32 {
+    let max = rect.width.min(rect.height);
+    let mut cut = 0;
+    // Pixels with i + j < cut intersect the cut triangle and have to be transparent.
+    while cut < max && (0..=cut).all(|i| !opaque(i, cut - i)) {
+        cut += 1;
+    }
+    if cut * cut * 8 >= rect.width * rect.height {
+        cut
+    } else {
+        0
+    }
+}
+
+fn get_pixels(rect: &IRect, bounds: &URect, tex: &Texture) -> Vec<Pixel> {
     let end_x = rect.x + rect.width as i32;
     let end_y = rect.y + rect.height as i32;
     let size = (rect.width * rect.height) as usize;
@@ -93,46 +287,52 @@ fn get_pixels(rect: &IRect, tex: &Texture) -> Vec<Pixel> {
     let mut idx = 0;
     for y in rect.y..end_y {
         for x in rect.x..end_x {
-            pixels[idx] = get_pixel(x, y, tex);
+            pixels[idx] = get_pixel(x, y, bounds, tex);
             idx += 1;
         }
     }
     pixels
 }
 
-fn get_pixel(x: i32, y: i32, tex: &Texture) -> Pixel {
-    let x = saturate(x, tex.width - 1);
-    let y = saturate(y, tex.height - 1);
+fn get_pixel(x: i32, y: i32, bounds: &URect, tex: &Texture) -> Pixel {
+    let x = saturate(x, bounds.x, bounds.x + bounds.width - 1);
+    let y = saturate(y, bounds.y, bounds.y + bounds.height - 1);
     tex.pixels[(x + tex.width * y) as usize]
 }
 
-fn pad_rect(rect: &IRect, pad: u32) -> IRect {
+fn pad_rect(rect: &IRect, cell: &Cell) -> IRect {
     IRect {
-        x: rect.x - pad as i32,
-        y: rect.y - pad as i32,
-        width: rect.width + pad * 2,
-        height: rect.height + pad * 2,
+        x: rect.x - cell.pad as i32,
+        y: rect.y - cell.pad as i32,
+        width: cell.padded_size(rect.width),
+        height: cell.padded_size(rect.height),
     }
 }
 
-fn crop_over_borders(rect: &IRect, tex: &Texture) -> URect {
-    URect {
-        x: rect.x as u32,
-        y: rect.y as u32,
-        width: cmp::min(rect.width, tex.width - rect.x as u32),
-        height: cmp::min(rect.height, tex.height - rect.y as u32),
-    }
+/// Limits the bounds to the unit rect padded by the specified (overridden) padding, so that
+/// the rest of the cell padding repeats the edge pixels instead of sampling the neighbours.
+fn limit_bounds(bounds: &URect, rect: &URect, pad: u32) -> URect {
+    let x = bounds.x.max(rect.x.saturating_sub(pad));
+    let y = bounds.y.max(rect.y.saturating_sub(pad));
+    let end_x = (bounds.x + bounds.width).min(rect.x + rect.width + pad);
+    let end_y = (bounds.y + bounds.height).min(rect.y + rect.height + pad);
+    URect::new(x, y, end_x - x, end_y - y)
 }
 
+/// Hashes the pixels with 64-bit FNV-1a over the RGBA bytes. The hashes are persisted with
+/// [AtlasCell::hash] and matched by incremental dicing, so the function must never change.
 fn hash(pixels: &[Pixel]) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    pixels.hash(&mut hasher);
-    hasher.finish()
+    let mut hash = 0xcbf29ce484222325;
+    for byte in pixels.iter().flat_map(|p| p.to_raw()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
 }
 
-fn saturate(n: i32, max: u32) -> u32 {
-    if n < 0 {
-        0
+fn saturate(n: i32, min: u32, max: u32) -> u32 {
+    if n < min as i32 {
+        min
     } else if n > max as i32 {
         max
     } else {
@@ -153,14 +353,47 @@ mod tests {
 
     #[test]
     fn errs_when_unit_size_zero() {
-        assert!(dice(&[src(&R1X1)], &pref(0, 0))
-            .is_err_and(|e| e.to_string() == "Unit size can't be zero."));
+        assert!(
+            dice(&[src(&R1X1)], &pref(0, 0)).is_err_and(|e| e.to_string()
+                == "Unit size can't be zero."
+                && e == Error::InvalidUnitSize {
+                    unit_size: 0,
+                    atlas_size_limit: 2048
+                })
+        );
     }
 
     #[test]
     fn errs_when_padding_is_above_unit_size() {
-        assert!(dice(&[src(&R1X1)], &pref(1, 2))
-            .is_err_and(|e| e.to_string() == "Padding can't be above unit size."));
+        assert!(
+            dice(&[src(&R1X1)], &pref(1, 2)).is_err_and(|e| e.to_string()
+                == "Padding (2) can't be above unit size (1)."
+                && e.code() == 3)
+        );
+    }
+
+    #[test]
+    fn errs_when_cancelled() {
+        let prefs = Prefs {
+            on_cancel: Some(Box::new(|| true)),
+            ..pref(1, 0)
+        };
+        assert!(dice(&[src(&R1X1)], &prefs).is_err_and(|e| e == Error::Cancelled));
+    }
+
+    #[test]
+    fn errs_when_mip_levels_exceed_atlas_size_limit() {
+        let prefs = Prefs {
+            mip_levels: 3,
+            atlas_size_limit: 4,
+            ..pref(1, 0)
+        };
+        assert!(dice(&[src(&R1X1)], &prefs).is_err_and(|e| e.to_string()
+            == "Mip levels (3) can't exceed atlas size limit (4)."
+            && e == Error::TooManyMipLevels {
+                mip_levels: 3,
+                atlas_size_limit: 4
+            }));
     }
 
     #[test]
@@ -263,6 +496,135 @@ mod tests {
             R, R, T]));
     }
 
+    #[test]
+    fn when_growing_padding_pixels_fill_aligned_cell() {
+        let prefs = Prefs {
+            block_alignment: 4,
+            grow_padding: true,
+            ..pref(1, 0)
+        };
+        let unit = dice(&[src(&BGRT)], &prefs).unwrap()[0].units[0].to_owned();
+        #[rustfmt::skip]
+        assert_eq!(unit.pixels, vec![
+            B, B, G, G,
+            B, B, G, G,
+            R, R, T, T,
+            R, R, T, T]);
+    }
+
+    #[test]
+    fn when_not_growing_padding_aligned_cell_doesnt_affect_pixels() {
+        let prefs = Prefs {
+            block_alignment: 4,
+            grow_padding: false,
+            ..pref(1, 0)
+        };
+        assert_eq!(
+            dice(&[src(&B1X1)], &prefs).unwrap()[0].units[0].pixels,
+            vec![B]
+        );
+    }
+
+    #[test]
+    fn units_dont_cross_slice_lines() {
+        let mut sprite = src(&RGB4X4);
+        sprite.border = Some(Border::new(1, 1, 3, 0));
+        let units = dice(&[sprite], &pref(2, 0)).unwrap().pop().unwrap().units;
+        let rects = units.iter().map(|u| u.rect.to_owned()).collect::<Vec<_>>();
+        assert_eq!(
+            rects,
+            vec![
+                URect::new(0, 0, 1, 2),
+                URect::new(0, 2, 1, 1),
+                URect::new(0, 3, 1, 1),
+                URect::new(1, 0, 2, 2),
+                URect::new(1, 2, 2, 1),
+                URect::new(1, 3, 2, 1),
+                URect::new(3, 0, 1, 2),
+                URect::new(3, 2, 1, 1),
+                URect::new(3, 3, 1, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn pixels_over_slice_lines_are_clamped() {
+        let mut sprite = src(&BGRT);
+        sprite.border = Some(Border::new(1, 0, 0, 0));
+        let units = dice(&[sprite], &pref(2, 1)).unwrap().pop().unwrap().units;
+        #[rustfmt::skip]
+        assert_eq!(units[0].pixels, vec![
+            B, B, B, B,
+            B, B, B, B,
+            R, R, R, R,
+            R, R, R, R]);
+        assert_eq!(units[0].rect, URect::new(0, 0, 1, 2));
+    }
+
+    #[test]
+    fn when_crop_is_off_unit_is_covered_whole() {
+        let unit = &dice1(&TTTM, 2, 0).units[0];
+        assert_eq!(unit.crop.rect, URect::new(0, 0, 2, 2));
+        assert_eq!(unit.crop.cuts, [0; 4]);
+    }
+
+    #[test]
+    fn when_cropped_to_rect_unit_is_covered_to_opaque_bounds() {
+        let prefs = Prefs {
+            alpha_crop: AlphaCrop::Rect,
+            ..pref(2, 0)
+        };
+        let crops = dice(&[src(&TTTM), src(&BTGT)], &prefs)
+            .unwrap()
+            .into_iter()
+            .map(|d| d.units[0].crop.rect.to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(crops, vec![URect::new(1, 1, 1, 1), URect::new(0, 0, 1, 2)]);
+    }
+
+    #[test]
+    fn when_cropped_to_polygon_only_worthy_corners_are_cut() {
+        #[rustfmt::skip]
+        let tex = Texture { width: 4, height: 4, pixels: vec![
+            T, B, B, T,
+            B, B, B, B,
+            T, B, B, B,
+            T, T, B, B,
+        ]};
+        let prefs = Prefs {
+            alpha_crop: AlphaCrop::Polygon,
+            ..pref(4, 0)
+        };
+        let crop = dice(&[src(&tex)], &prefs).unwrap()[0].units[0]
+            .crop
+            .to_owned();
+        assert_eq!(crop.rect, URect::new(0, 0, 4, 4));
+        assert_eq!(crop.cuts, [0, 2, 0, 0]);
+        assert_eq!(crop.vertices(), 5);
+        assert_eq!(crop.indices(), 9);
+    }
+
+    #[test]
+    fn units_are_opaque_when_covered_pixels_are_opaque() {
+        let half = Pixel::new(255, 0, 0, 128);
+        let tex = Texture {
+            width: 2,
+            height: 1,
+            pixels: vec![half, R],
+        };
+        let units = dice1(&tex, 1, 0).units;
+        assert_eq!(
+            units.iter().map(|u| u.opaque).collect::<Vec<_>>(),
+            [false, true]
+        );
+        assert!(!dice1(&TTTM, 2, 0).units[0].opaque);
+        let prefs = Prefs {
+            alpha_crop: AlphaCrop::Rect,
+            ..pref(2, 0)
+        };
+        assert!(dice(&[src(&TTTM)], &prefs).unwrap()[0].units[0].opaque);
+    }
+
     #[test]
     fn diced_texture_contains_identical_units() {
         assert_eq!(16, dice1(&RGB4X4, 1, 0).units.len());
@@ -275,6 +637,53 @@ mod tests {
         assert_eq!(16, dice1(&PLT4X4, 1, 0).unique.len());
     }
 
+    #[test]
+    fn sprite_padding_limits_sampled_neighbours() {
+        let mut sprite = src(&RGB4X4);
+        let prefs = pref(1, 1);
+        let padded = dice(&[sprite.clone()], &prefs).unwrap().pop().unwrap();
+        sprite.padding = Some(0);
+        let clamped = dice(&[sprite], &prefs).unwrap().pop().unwrap();
+        for (padded, clamped) in padded.units.iter().zip(clamped.units.iter()) {
+            assert_eq!(padded.hash, clamped.hash);
+            // The 3x3 padded unit pixels are all repeating the unit content (center).
+            assert!(clamped.pixels.iter().all(|p| *p == clamped.pixels[4]));
+        }
+        assert!(padded
+            .units
+            .iter()
+            .any(|u| u.pixels.iter().any(|p| *p != u.pixels[4])));
+    }
+
+    #[test]
+    fn sprite_ppu_and_trim_are_passed_to_diced_texture() {
+        let mut sprite = src(&RGB4X4);
+        sprite.ppu = Some(2.0);
+        sprite.trim_transparent = Some(false);
+        let diced = dice(&[sprite], &pref(1, 0)).unwrap().pop().unwrap();
+        assert_eq!(diced.ppu, Some(2.0));
+        assert_eq!(diced.trim_transparent, Some(false));
+    }
+
+    #[test]
+    fn outlines_are_traced_when_tolerance_specified() {
+        assert!(dice1(&BGRT, 1, 0).outlines.is_empty());
+        let prefs = Prefs {
+            outline_tolerance: Some(0.0),
+            ..pref(1, 0)
+        };
+        let diced = dice(&[src(&BGRT)], &prefs).unwrap().pop().unwrap();
+        assert_eq!(
+            diced.outlines,
+            vec![vec![(0, 0), (0, 2), (1, 2), (1, 1), (2, 1), (2, 0)]]
+        );
+    }
+
+    #[test]
+    fn unit_hashes_are_stable() {
+        assert_eq!(dice1(&B1X1, 1, 0).units[0].hash, 0x4a3d077f9b55736b);
+    }
+
     #[test]
     fn reports_progress() {
         let progress = sample_progress(|p| drop(dice(&[src(&B1X1)], &p)));
@@ -295,11 +704,7 @@ mod tests {
     }
 
     fn src(tex: &Texture) -> SourceSprite {
-        SourceSprite {
-            id: "test".to_string(),
-            texture: tex.to_owned(),
-            pivot: None,
-        }
+        SourceSprite::new("test", tex.to_owned())
     }
 
     fn is_opaque(tex: &DicedTexture) -> bool {
@@ -94,9 +94,8 @@ pub trait AnySource {
     fn pivot(&self) -> Option<Pivot>;
     fn sprite(&self) -> SourceSprite {
         SourceSprite {
-            id: "TEST".to_string(),
-            texture: self.texture(),
             pivot: self.pivot(),
+            ..SourceSprite::new("TEST", self.texture())
         }
     }
 }
@@ -110,6 +109,15 @@ impl AnySource for LazyLock<Texture> {
     }
 }
 
+impl AnySource for Texture {
+    fn texture(&self) -> Texture {
+        self.to_owned()
+    }
+    fn pivot(&self) -> Option<Pivot> {
+        None
+    }
+}
+
 impl AnySource for (&LazyLock<Texture>, (f32, f32)) {
     fn texture(&self) -> Texture {
         (self.0 as &Texture).to_owned()
@@ -132,7 +140,7 @@ fn tex(width: u32, height: u32, pixels: Vec<Pixel>) -> Texture {
 
 fn palette(width: u32, height: u32) -> Texture {
     let mut pixels = Vec::new();
-    for i in 0..=(width * height) as u8 {
+    for i in 0..(width * height) as u8 {
         pixels.push(Pixel::new(i, i, i, 255))
     }
     tex(width, height, pixels)
@@ -1,10 +1,19 @@
+mod bleed;
+mod buffers;
 mod builder;
 mod dicer;
 mod fixtures;
 mod models;
+mod optimizer;
+mod outliner;
 mod packer;
+mod stats;
+mod tinter;
+mod validator;
+mod verifier;
 
 pub use models::*;
+use std::collections::{HashMap, HashSet};
 
 /// Splits specified sprite textures into chunks, discards identical ones, joins unique
 /// chunks into atlas textures and generates sprite meshes with texture coordinates mapped
@@ -34,8 +43,8 @@ pub use models::*;
 ///
 /// // Collect source sprites to dice.
 /// let sprites = vec![
-///     SourceSprite { id: "1".to_owned(), texture: load("1.png"), pivot: None },
-///     SourceSprite { id: "2".to_owned(), texture: load("2.png"), pivot: None },
+///     SourceSprite::new("1", load("1.png")),
+///     SourceSprite::new("2", load("2.png")),
 ///     // ...
 /// ];
 ///
@@ -65,9 +74,242 @@ pub use models::*;
 /// }
 /// ```
 pub fn dice(sprites: &[SourceSprite], prefs: &Prefs) -> Result<Artifacts> {
+    validator::validate(sprites, prefs)?;
     let diced = dicer::dice(sprites, prefs)?;
     let packed = packer::pack(diced, prefs)?;
-    let sprites = builder::build(&packed, prefs)?;
-    let atlases = packed.into_iter().map(|p| p.texture).collect();
-    Ok(Artifacts { atlases, sprites })
+    assemble(sprites, packed, prefs)
+}
+
+/// Same as [dice], but keeps the units of the atlases generated by a previous dicing operation
+/// in place, so that the atlas textures and diced sprites which content didn't change stay
+/// intact; useful to minimize version control diffs and patch sizes. New units are placed into
+/// the free cells of the previous atlases (including cells of the units no longer used) or
+/// into new atlases appended after the previous ones; the atlas sizes don't change.
+///
+/// # Arguments
+///
+/// * `sprites`: Source sprite textures to dice.
+/// * `previous`: Products of the previous dicing operation.
+/// * `prefs`: User preferences for the dicing operation; should be the same as for the
+///   previous operation, otherwise the units may not be reused.
+///
+/// returns: Generated atlas textures and diced sprite meshes along with the changes relative
+/// to the previous operation or [Error].
+pub fn dice_incremental(
+    sprites: &[SourceSprite],
+    previous: &Artifacts,
+    prefs: &Prefs,
+) -> Result<Incremental> {
+    validator::validate(sprites, prefs)?;
+    let diced = dicer::dice(sprites, prefs)?;
+    let packed = packer::repack(diced, previous, false, prefs)?;
+    let artifacts = assemble(sprites, packed, prefs)?;
+    Ok(compare(previous, artifacts))
+}
+
+/// Same as [dice], but reuses units of read-only base atlases generated by another dicing
+/// operation (eg, atlases of the base game when dicing content of a DLC or patch). The base
+/// atlases are returned intact in front of the new atlases in [Artifacts::atlases], so that
+/// [DicedSprite::atlas_index] below the number of the base atlases references a base atlas.
+/// The units missing on the base atlases are packed into the new atlases; the sprites mixing
+/// base and new units span multiple atlases, as specified by [DicedSprite::atlas_indices].
+///
+/// # Arguments
+///
+/// * `sprites`: Source sprite textures to dice.
+/// * `base`: Products of the base dicing operation; only the atlases and cells are used.
+/// * `prefs`: User preferences for the dicing operation; unit size and padding should be
+///   the same as for the base operation.
+///
+/// returns: Base and new atlas textures and diced sprite meshes or [Error].
+pub fn dice_with_base(
+    sprites: &[SourceSprite],
+    base: &Artifacts,
+    prefs: &Prefs,
+) -> Result<Artifacts> {
+    validator::validate(sprites, prefs)?;
+    let diced = dicer::dice(sprites, prefs)?;
+    let packed = packer::repack(diced, base, true, prefs)?;
+    assemble(sprites, packed, prefs)
+}
+
+/// Evaluates products of dicing specified sprite textures without baking the atlas textures
+/// and building the meshes. Useful for picking optimal [Prefs] in a fraction of the time
+/// required by the full [dice] operation.
+///
+/// # Arguments
+///
+/// * `sprites`: Source sprite textures to evaluate.
+/// * `prefs`: User preferences for the dicing operation.
+///
+/// returns: Projected atlas dimensions, unique unit and mesh element counts or [Error].
+pub fn estimate(sprites: &[SourceSprite], prefs: &Prefs) -> Result<Estimate> {
+    validator::validate(sprites, prefs)?;
+    let diced = dicer::dice(sprites, prefs)?;
+    let unique_units = diced
+        .iter()
+        .flat_map(|d| &d.unique)
+        .collect::<HashSet<_>>()
+        .len();
+    // Duplicates emitted as aliases don't contribute to the mesh.
+    let mut fingerprints = HashSet::new();
+    let meshes = diced
+        .iter()
+        .filter(|d| !prefs.alias_duplicates || fingerprints.insert(builder::fingerprint(d)))
+        .collect::<Vec<_>>();
+    let crops = meshes.iter().flat_map(|d| &d.units).map(|u| &u.crop);
+    let vertices = crops.clone().map(Crop::vertices).sum::<usize>();
+    let indices = crops.map(Crop::indices).sum::<usize>();
+    let plans = packer::plan(diced, prefs)?;
+    let atlases = plans.into_iter().map(|p| p.size).collect::<Vec<_>>();
+    Ok(Estimate {
+        atlas_bytes: atlases
+            .iter()
+            .map(|a| (a.width * a.height) as usize * 4)
+            .sum(),
+        mesh_bytes: stats::mesh_bytes(vertices, indices),
+        atlases,
+        unique_units,
+        vertices,
+        indices,
+    })
+}
+
+/// Searches for the unit size and padding yielding the lowest cost when dicing specified
+/// sprite textures. Each candidate is evaluated with [estimate], so the search is much faster
+/// than running the full [dice] operation for each of them.
+///
+/// # Arguments
+///
+/// * `sprites`: Source sprite textures to evaluate.
+/// * `prefs`: Base preferences for the dicing operation; unit size and padding are searched.
+/// * `search`: Candidate unit sizes and paddings to evaluate and the cost model to score them.
+///
+/// returns: Preferences with the optimal unit size and padding and all the evaluated
+/// candidates or [Error] when none of the candidates are valid.
+pub fn optimize(sprites: &[SourceSprite], prefs: Prefs, search: &Search) -> Result<Optimized> {
+    optimizer::optimize(sprites, prefs, search)
+}
+
+/// Rasterizes specified diced sprite back into a texture, sampling the associated atlas
+/// textures with the sprite mesh UVs (nearest neighbour). The pixels are positioned as on the
+/// source texture and the texture has the source size, including the trimmed transparent area.
+///
+/// # Arguments
+///
+/// * `sprite`: Diced sprite to reconstruct; resolve aliases with [Artifacts::resolve] first.
+/// * `atlases`: Atlas textures of the dicing [Artifacts] the sprite belongs to; indexed by
+///   [DicedSprite::atlas_index] and [DicedSprite::atlas_indices].
+/// * `prefs`: Preferences used when dicing the sprite; the coordinates are honored.
+///
+/// returns: Reconstructed texture of the source sprite.
+pub fn reconstruct(sprite: &DicedSprite, atlases: &[Texture], prefs: &Prefs) -> Texture {
+    verifier::reconstruct(sprite, atlases, prefs)
+}
+
+/// Reconstructs the diced sprites with [reconstruct] and compares them with the source
+/// sprites pixel by pixel; fully transparent pixels are considered equal regardless of color.
+/// Use to make sure the dicing is lossless, eg when picking [Prefs] or after encoding atlases.
+///
+/// # Arguments
+///
+/// * `sources`: Source sprites specified for the dicing operation.
+/// * `arts`: Products of the dicing operation.
+/// * `prefs`: Preferences used for the dicing operation.
+///
+/// returns: Mismatched sprites; empty when all the sprites are reconstructed losslessly.
+pub fn verify(sources: &[SourceSprite], arts: &Artifacts, prefs: &Prefs) -> Vec<Mismatch> {
+    verifier::verify(sources, arts, prefs)
+}
+
+/// Analyzes texture bleeding of the diced sprites rendered with bilinear filtering at the
+/// specified scales and mip levels. Each diced sprite is sampled around the edges of its units
+/// and compared with the source texture sampled at the same positions. Seams are reported per
+/// sprite, along with the minimum padding and UV inset removing them.
+///
+/// # Arguments
+///
+/// * `sprites`: Source sprite textures to analyze.
+/// * `prefs`: Preferences for the dicing operation; padding and UV inset are searched.
+/// * `check`: Sampling conditions to analyze and the search range of padding and UV inset.
+///
+/// returns: Seams of the diced sprites and recommended padding and UV inset or [Error].
+pub fn analyze_bleed(sprites: &[SourceSprite], prefs: Prefs, check: &BleedCheck) -> Result<Bleed> {
+    bleed::analyze(sprites, prefs, check)
+}
+
+/// Packs mesh of the specified diced sprite into GPU-ready buffers: vertex buffer with
+/// interleaved position, UV and color (when the sprite has [DicedSprite::colors]) and index
+/// buffer with the smallest index width fitting the vertex count.
+///
+/// # Arguments
+///
+/// * `sprite`: Diced sprite which mesh to pack.
+/// * `uv_format`: Format in which to encode UV components of the vertices.
+///
+/// returns: Vertex and index buffers accessible as raw bytes.
+pub fn to_buffers(sprite: &DicedSprite, uv_format: UvFormat) -> MeshBuffers {
+    buffers::pack(sprite, uv_format)
+}
+
+fn assemble(sprites: &[SourceSprite], packed: Vec<Atlas>, prefs: &Prefs) -> Result<Artifacts> {
+    let built = builder::build(&packed, prefs)?;
+    let stats = prefs
+        .collect_stats
+        .then(|| stats::collect(sprites, &packed, &built, prefs));
+    let mut atlases = Vec::with_capacity(packed.len());
+    let mut mips = Vec::with_capacity(packed.len());
+    let mut cells = Vec::with_capacity(packed.len());
+    for atlas in packed {
+        atlases.push(atlas.texture);
+        mips.push(atlas.mips);
+        cells.push(atlas.cells);
+    }
+    Ok(Artifacts {
+        atlases,
+        mips,
+        cells,
+        sprites: built,
+        stats,
+    })
+}
+
+fn compare(previous: &Artifacts, artifacts: Artifacts) -> Incremental {
+    let prev_sprites = previous.sprites.iter().map(|s| (&s.id, s));
+    let prev_sprites = prev_sprites.collect::<HashMap<_, _>>();
+    let ids = artifacts
+        .sprites
+        .iter()
+        .map(|s| &s.id)
+        .collect::<HashSet<_>>();
+    let sprites = artifacts.sprites.iter().map(|s| (&s.id, s));
+    let sprites = sprites.collect::<HashMap<_, _>>();
+    let mut changed_sprites = vec![];
+    for sprite in artifacts.sprites.iter() {
+        let changed = prev_sprites.get(&sprite.id).is_none_or(|prev| {
+            // Meshes of the aliases change along with the original sprites.
+            *prev != sprite
+                || !builder::is_same_mesh(resolve(&prev_sprites, prev), resolve(&sprites, sprite))
+        });
+        if changed {
+            changed_sprites.push(sprite.id.to_owned());
+        }
+    }
+    let removed_sprites = previous.sprites.iter().filter(|s| !ids.contains(&s.id));
+    let changed_atlases = artifacts.atlases.iter().enumerate();
+    let changed_atlases = changed_atlases.filter(|(idx, a)| previous.atlases.get(*idx) != Some(a));
+    Incremental {
+        changed_sprites,
+        removed_sprites: removed_sprites.map(|s| s.id.to_owned()).collect(),
+        changed_atlases: changed_atlases.map(|(idx, _)| idx).collect(),
+        artifacts,
+    }
+}
+
+fn resolve<'a>(
+    sprites: &HashMap<&String, &'a DicedSprite>,
+    sprite: &'a DicedSprite,
+) -> &'a DicedSprite {
+    let original = sprite.alias_of.as_ref().and_then(|id| sprites.get(id));
+    original.copied().unwrap_or(sprite)
 }
@@ -1,21 +1,238 @@
 //! Common data models.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
 
 /// Result of a dicing operation.
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// Error occurred in a dicing operation.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Error {
-    /// An issue with [Prefs] and/or input data.
-    Spec(&'static str),
+    /// [Prefs::unit_size] is zero or above [Prefs::atlas_size_limit].
+    InvalidUnitSize {
+        unit_size: u32,
+        atlas_size_limit: u32,
+    },
+    /// [Prefs::padding] is above [Prefs::unit_size].
+    PaddingTooLarge { padding: u32, unit_size: u32 },
+    /// Unique units of the sprite exceed capacity of a single atlas.
+    DoesntFit {
+        sprite_id: String,
+        units: usize,
+        capacity: u32,
+    },
+    /// Pivot is not a finite number; sprite id is [None] when the pivot is [Prefs::pivot].
+    InvalidPivot {
+        sprite_id: Option<String>,
+        x: f32,
+        y: f32,
+    },
+    /// The sprite texture has zero width or height, or number of its pixels doesn't match
+    /// the texture dimensions.
+    MalformedTexture {
+        sprite_id: String,
+        width: u32,
+        height: u32,
+        pixels: usize,
+    },
+    /// The operation was aborted via [Prefs::on_cancel].
+    Cancelled,
+    /// ID of the source sprite with the specified index is empty.
+    EmptyId { index: usize },
+    /// Multiple source sprites have the same ID.
+    DuplicateId { sprite_id: String },
+    /// Opposite sides of the sprite [Border] overlap, ie exceed the texture dimensions.
+    InvalidBorder { sprite_id: String, border: Border },
+    /// PPU override of the sprite ([SourceSprite::ppu]) is not a positive finite number.
+    InvalidSpritePpu { sprite_id: String, ppu: f32 },
+    /// The frame differs from the first frame of its sequence in texture size, PPU or trim.
+    MismatchedSequence { sprite_id: String, sequence: String },
+    /// [Prefs::ppu] is not a positive finite number.
+    InvalidPpu { ppu: f32 },
+    /// [Prefs::uv_inset] is out of the 0.0 to 0.5 range.
+    InvalidUvInset { uv_inset: f32 },
+    /// [Prefs::atlas_size_limit] is zero.
+    ZeroAtlasSizeLimit,
+    /// Atlas of [Prefs::atlas_size_limit] can't be downsampled [Prefs::mip_levels] times.
+    TooManyMipLevels {
+        mip_levels: u32,
+        atlas_size_limit: u32,
+    },
+    /// [Prefs::outline_tolerance] is negative or not a finite number.
+    InvalidOutlineTolerance { tolerance: f32 },
+    /// Unit rect of a cell in the previous [Artifacts] is off the atlas grid evaluated with
+    /// the unit size and padding in [Prefs], or outside the atlas.
+    MismatchedCell { atlas_index: usize, rect: URect },
+    /// Padding override of the sprite ([SourceSprite::padding]) is above [Prefs::padding].
+    SpritePaddingTooLarge {
+        sprite_id: String,
+        padding: u32,
+        max_padding: u32,
+    },
+    /// [Search::unit_sizes] is empty.
+    EmptySearch,
+    /// [BleedCheck::scales] or [BleedCheck::mip_levels] is empty.
+    EmptyBleedCheck,
+    /// One of the [BleedCheck::scales] is not a positive finite number.
+    InvalidBleedScale { scale: f32 },
+    /// [BleedCheck::uv_inset_step] is not a positive finite number.
+    InvalidInsetStep { step: f32 },
+}
+
+impl Error {
+    /// Stable numeric code of the error kind; 0 is reserved for success and 1 for the former
+    /// generic error, superseded by the specific variants.
+    pub fn code(&self) -> u32 {
+        match self {
+            Error::InvalidUnitSize { .. } => 2,
+            Error::PaddingTooLarge { .. } => 3,
+            Error::DoesntFit { .. } => 4,
+            Error::InvalidPivot { .. } => 5,
+            Error::MalformedTexture { .. } => 6,
+            Error::Cancelled => 7,
+            Error::EmptyId { .. } => 8,
+            Error::DuplicateId { .. } => 9,
+            Error::InvalidBorder { .. } => 10,
+            Error::InvalidSpritePpu { .. } => 11,
+            Error::MismatchedSequence { .. } => 12,
+            Error::InvalidPpu { .. } => 13,
+            Error::InvalidUvInset { .. } => 14,
+            Error::ZeroAtlasSizeLimit => 15,
+            Error::TooManyMipLevels { .. } => 16,
+            Error::InvalidOutlineTolerance { .. } => 17,
+            Error::MismatchedCell { .. } => 18,
+            Error::SpritePaddingTooLarge { .. } => 19,
+            Error::EmptySearch => 20,
+            Error::EmptyBleedCheck => 21,
+            Error::InvalidBleedScale { .. } => 22,
+            Error::InvalidInsetStep { .. } => 23,
+        }
+    }
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::Spec(info) => write!(f, "{}", info),
+            Error::InvalidUnitSize { unit_size: 0, .. } => write!(f, "Unit size can't be zero."),
+            Error::InvalidUnitSize {
+                unit_size,
+                atlas_size_limit,
+            } => write!(
+                f,
+                "Unit size ({unit_size}) can't be above atlas size limit ({atlas_size_limit})."
+            ),
+            Error::PaddingTooLarge { padding, unit_size } => write!(
+                f,
+                "Padding ({padding}) can't be above unit size ({unit_size})."
+            ),
+            Error::DoesntFit {
+                sprite_id,
+                units,
+                capacity,
+            } => write!(
+                f,
+                "Can't fit sprite '{sprite_id}' ({units} unique units) into single atlas \
+                ({capacity} units max); increase atlas size limit."
+            ),
+            Error::InvalidPivot {
+                sprite_id: Some(id),
+                x,
+                y,
+            } => write!(
+                f,
+                "Pivot ({x}, {y}) of sprite '{id}' is not a finite number."
+            ),
+            Error::InvalidPivot { x, y, .. } => {
+                write!(f, "Pivot ({x}, {y}) is not a finite number.")
+            }
+            Error::MalformedTexture {
+                sprite_id,
+                width,
+                height,
+                ..
+            } if *width == 0 || *height == 0 => write!(
+                f,
+                "Texture of sprite '{sprite_id}' has zero size ({width}x{height})."
+            ),
+            Error::MalformedTexture {
+                sprite_id,
+                width,
+                height,
+                pixels,
+            } => write!(
+                f,
+                "Texture of sprite '{sprite_id}' has {pixels} pixels, \
+                while {width}x{height} is expected."
+            ),
+            Error::Cancelled => write!(f, "Operation was cancelled."),
+            Error::EmptyId { index } => write!(f, "ID of source sprite #{index} is empty."),
+            Error::DuplicateId { sprite_id } => {
+                write!(f, "Multiple source sprites have '{sprite_id}' ID.")
+            }
+            Error::InvalidBorder { sprite_id, border } => write!(
+                f,
+                "Border ({}, {}, {}, {}) of sprite '{sprite_id}' exceeds its texture dimensions.",
+                border.left, border.right, border.top, border.bottom
+            ),
+            Error::InvalidSpritePpu { sprite_id, ppu } => write!(
+                f,
+                "PPU ({ppu}) of sprite '{sprite_id}' should be a positive finite number."
+            ),
+            Error::MismatchedSequence {
+                sprite_id,
+                sequence,
+            } => write!(
+                f,
+                "Sprite '{sprite_id}' differs from the first frame of sequence '{sequence}' \
+                in texture size, PPU or trim."
+            ),
+            Error::InvalidPpu { ppu } => {
+                write!(f, "PPU ({ppu}) should be a positive finite number.")
+            }
+            Error::InvalidUvInset { uv_inset } => {
+                write!(f, "UV inset ({uv_inset}) should be in 0.0 to 0.5 range.")
+            }
+            Error::ZeroAtlasSizeLimit => write!(f, "Atlas size limit can't be zero."),
+            Error::TooManyMipLevels {
+                mip_levels,
+                atlas_size_limit,
+            } => write!(
+                f,
+                "Mip levels ({mip_levels}) can't exceed atlas size limit ({atlas_size_limit})."
+            ),
+            Error::InvalidOutlineTolerance { tolerance } => write!(
+                f,
+                "Outline tolerance ({tolerance}) should be a non-negative finite number."
+            ),
+            Error::MismatchedCell { atlas_index, rect } => write!(
+                f,
+                "Cell ({}, {}, {}x{}) of atlas #{atlas_index} doesn't match the atlas grid; \
+                make sure the unit size and padding are the same as in the previous dicing.",
+                rect.x, rect.y, rect.width, rect.height
+            ),
+            Error::SpritePaddingTooLarge {
+                sprite_id,
+                padding,
+                max_padding,
+            } => write!(
+                f,
+                "Padding ({padding}) of sprite '{sprite_id}' can't be above \
+                the padding in prefs ({max_padding})."
+            ),
+            Error::EmptySearch => write!(f, "Specify at least one unit size to search."),
+            Error::EmptyBleedCheck => {
+                write!(f, "Specify at least one scale and mip level to analyze.")
+            }
+            Error::InvalidBleedScale { scale } => {
+                write!(f, "Scale ({scale}) should be a positive finite number.")
+            }
+            Error::InvalidInsetStep { step } => {
+                write!(
+                    f,
+                    "UV inset step ({step}) should be a positive finite number."
+                )
+            }
         }
     }
 }
@@ -31,6 +248,18 @@ pub struct Prefs {
     /// Increase to prevent texture bleeding artifacts. Larger values consume more texture space,
     /// but yield better anti-bleeding results.
     pub padding: u32,
+    /// Size of the GPU compression blocks (eg, 4 for BC7 and ETC2, 4-12 for ASTC), in pixels.
+    /// Padded units are placed into atlas cells rounded up to the block size, so that the
+    /// compression artifacts of a block don't bleed over the adjacent units; 0 or 1 disables alignment.
+    pub block_alignment: u32,
+    /// Whether to grow the padding to fill the extra space in the atlas cells caused by the
+    /// [block_alignment] or [mip_levels]. When disabled, the extra space is left transparent.
+    pub grow_padding: bool,
+    /// Number of mip levels to generate for the atlas textures; 0 disables mip generation.
+    /// The atlas cells are aligned to power of two boundaries, so that the units don't blend
+    /// with each other when sampled at the lower mip levels. Enable [grow_padding] to prevent
+    /// the units from blending with the transparent areas of the aligned cells.
+    pub mip_levels: u32,
     /// Relative inset (in 0.0-1.0 range) of the diced units UV coordinates. Can be used in
     /// addition to (or instead of) [padding] to prevent texture bleeding artifacts. Won't
     /// consume texture space, but higher values could visually distort the rendered sprite.
@@ -38,6 +267,20 @@ pub struct Prefs {
     /// Whether to trim transparent areas on the built meshes.
     /// Disable to preserve aspect ratio of the source sprites (usable for animations).
     pub trim_transparent: bool,
+    /// Whether and how to crop the mesh quads of the diced units to their opaque pixels.
+    /// Cropping reduces overdraw (fill rate) at the cost of the occasional extra vertices.
+    pub alpha_crop: AlphaCrop,
+    /// Max deviation, in pixels, of the simplified [DicedSprite::outlines] from the traced
+    /// alpha boundaries; 0 preserves the pixel-precise boundaries; None disables the outlines.
+    pub outline_tolerance: Option<f32>,
+    /// Whether to merge the units, which are exact tinted (multiplied by a color) copies of other
+    /// units: the copies reuse atlas cells of the originals, while the tint is carried in the
+    /// [DicedSprite::colors]. Only the units reconstructed exactly are merged.
+    pub dedupe_tints: bool,
+    /// Whether to emit the sprites with the same diced content and mesh as an earlier sprite
+    /// (eg, repeated animation frames or copied icons) as aliases referencing the mesh of the
+    /// earlier sprite via [DicedSprite::alias_of]; the mesh vectors of the aliases are empty.
+    pub alias_duplicates: bool,
     /// Maximum size (width or height) of a single generated atlas texture; will generate
     /// multiple textures when the limit is reached.
     pub atlas_size_limit: u32,
@@ -47,13 +290,25 @@ pub struct Prefs {
     /// The generated atlas textures will always have width and height be power of two.
     /// Extremely inefficient, but required by some older GPUs.
     pub atlas_pot: bool,
+    /// Order in which the diced units are laid out inside the atlas textures. Grouping related
+    /// content together yields better compression ratio of the encoded atlas textures.
+    pub unit_order: UnitOrder,
+    /// Whether to collect [Stats] of the dicing operation, such as units reuse, atlas occupancy
+    /// and source versus output size; the stats are returned with the [Artifacts].
+    pub collect_stats: bool,
     /// Pixel per unit ratio to use when evaluating positions of the generated mesh vertices.
     /// Higher values will make sprite smaller in conventional space units.
     pub ppu: f32,
-    /// Origin of the generated mesh, in relative offsets from top-left corner of the sprite rect.
+    /// Origin of the generated mesh, in relative offsets from top-left corner of the sprite rect
+    /// (bottom-left when [Coordinates::y_axis] is [YAxis::Up]).
     pub pivot: Pivot,
+    /// Conventions of the coordinate spaces in which the diced sprites are generated.
+    pub coordinates: Coordinates,
     /// Callback to invoke when dicing operation progress changes in a meaningful way.
     pub on_progress: Option<ProgressCallback>,
+    /// Callback polled each time the progress is reported; when returns true, the dicing
+    /// operation is aborted with [Error::Cancelled].
+    pub on_cancel: Option<CancelCallback>,
 }
 
 impl Default for Prefs {
@@ -61,21 +316,102 @@ impl Default for Prefs {
         Self {
             unit_size: 64,
             padding: 2,
+            block_alignment: 1,
+            grow_padding: false,
+            mip_levels: 0,
             uv_inset: 0.0,
             trim_transparent: true,
+            alpha_crop: AlphaCrop::Off,
+            outline_tolerance: None,
+            dedupe_tints: false,
+            alias_duplicates: false,
             atlas_size_limit: 2048,
             atlas_square: false,
             atlas_pot: false,
+            unit_order: UnitOrder::Hash,
+            collect_stats: false,
             ppu: 100.0,
             pivot: Pivot { x: 0.5, y: 0.5 },
+            coordinates: Coordinates::default(),
             on_progress: None,
+            on_cancel: None,
         }
     }
 }
 
+/// Cropping of the mesh quads emitted for the diced units.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum AlphaCrop {
+    /// Each quad covers the whole unit, including its transparent pixels.
+    #[default]
+    Off,
+    /// Each quad (along with the UVs) is shrunk to the bounds of the opaque unit pixels.
+    Rect,
+    /// Same as [AlphaCrop::Rect], but the transparent corners of the bounds are also cut
+    /// diagonally when the saved area is worth the extra vertices; each quad becomes
+    /// a convex polygon of up to 8 vertices.
+    Polygon,
+}
+
+/// Order in which diced units are laid out inside atlas textures.
+/// All the orders are deterministic: identical input always produces identical atlases.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum UnitOrder {
+    /// Order by content hashes of the units. Fastest, but scatters related content.
+    Hash,
+    /// Order by position of the units on the source textures, keeping neighbours together.
+    Source,
+    /// Order by average color of the units, keeping similar content together.
+    Color,
+}
+
+/// Conventions of the coordinate spaces in which the diced sprites are generated. Default
+/// conventions have Y-axis pointing down, UV origin at the top-left corner of the atlas and
+/// counter-clockwise triangles.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct Coordinates {
+    /// Direction of the Y-axis; applied to the mesh vertices, rect and pivot.
+    pub y_axis: YAxis,
+    /// Corner of the atlas texture where the UV coordinates originate.
+    pub uv_origin: UvOrigin,
+    /// Order of the mesh triangle vertices, as seen when the sprite is displayed upright.
+    pub winding: Winding,
+}
+
+/// Direction of the Y-axis in the diced sprite local space.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum YAxis {
+    /// Y grows downward, as in the source texture space.
+    #[default]
+    Down,
+    /// Y grows upward, as in most 2D engines.
+    Up,
+}
+
+/// Corner of the atlas texture where the UV coordinates originate (are 0,0).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum UvOrigin {
+    /// V grows downward from the top row of the atlas texture (DirectX, Vulkan).
+    #[default]
+    TopLeft,
+    /// V grows upward from the bottom row of the atlas texture (OpenGL).
+    BottomLeft,
+}
+
+/// Order of the mesh triangle vertices, which determines the front face.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Winding {
+    #[default]
+    CounterClockwise,
+    Clockwise,
+}
+
 /// Callback for notifying on dicing progress updates.
 pub type ProgressCallback = Box<dyn Fn(Progress)>;
 
+/// Callback for checking whether dicing operation should be cancelled.
+pub type CancelCallback = Box<dyn Fn() -> bool>;
+
 /// Progress of a dicing operation.
 #[derive(Debug, Clone)]
 pub struct Progress {
@@ -86,7 +422,9 @@ pub struct Progress {
 }
 
 impl Progress {
-    pub fn report(prefs: &Prefs, stage: u8, idx: usize, len: usize, activity: &str) {
+    /// Notifies [Prefs::on_progress] on the progress of a dicing stage and checks whether
+    /// the operation was cancelled via [Prefs::on_cancel].
+    pub fn report(prefs: &Prefs, stage: u8, idx: usize, len: usize, activity: &str) -> Result<()> {
         // Stages:
         // 0 Decoding source textures (cli only)
         // 1 Dicing source textures
@@ -99,6 +437,10 @@ impl Progress {
             let activity = format!("{activity}... ({num} of {len})");
             cb(Progress { ratio, activity });
         }
+        match &prefs.on_cancel {
+            Some(cancel) if cancel() => Err(Error::Cancelled),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -131,7 +473,7 @@ impl Pixel {
 }
 
 /// A set of pixels forming sprite texture.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Texture {
     /// Width of the texture, in pixels.
     pub width: u32,
@@ -153,43 +495,466 @@ pub struct SourceSprite {
     /// Relative position of the sprite origin point on the generated mesh.
     /// When not specified, will use default pivot specified in [Prefs].
     pub pivot: Option<Pivot>,
+    /// Arbitrary user data (eg, tags or localization keys) carried to the [DicedSprite] as is.
+    pub metadata: Metadata,
+    /// 9-slice border of the sprite; when specified, the units are aligned to the slice
+    /// lines and the mesh vertices are tagged with [DicedSprite::slices].
+    pub border: Option<Border>,
+    /// Pixel per unit ratio of the sprite mesh; when not specified, will use [Prefs::ppu].
+    pub ppu: Option<f32>,
+    /// Whether to trim transparent areas on the sprite mesh; when not specified,
+    /// will use [Prefs::trim_transparent].
+    pub trim_transparent: Option<bool>,
+    /// Padding of the sprite units, in pixels; when not specified, will use [Prefs::padding].
+    /// Can't exceed [Prefs::padding], as the units of all the sprites share the same atlas
+    /// cells; the rest of the cell padding repeats the edge pixels of the padded unit.
+    pub padding: Option<u32>,
+    /// Identifier of the animation sequence the sprite is a frame of, if any. Trimmed frames
+    /// of a sequence share the union of their bounds (instead of being trimmed separately)
+    /// and the pivot of the first frame, so that the frames stay aligned when played.
+    /// The frames should have the same texture size, [ppu] and [trim_transparent].
+    pub sequence: Option<String>,
 }
 
+impl SourceSprite {
+    /// Creates a sprite with specified ID and texture, empty metadata and the rest of the
+    /// properties not specified; override them with struct update syntax when required.
+    pub fn new(id: impl Into<String>, texture: Texture) -> Self {
+        SourceSprite {
+            id: id.into(),
+            texture,
+            pivot: None,
+            metadata: Metadata::new(),
+            border: None,
+            ppu: None,
+            trim_transparent: None,
+            padding: None,
+            sequence: None,
+        }
+    }
+}
+
+/// Arbitrary user data associated with a sprite, as key/value pairs ordered by key.
+pub type Metadata = BTreeMap<String, String>;
+
 /// Final products of a dicing operation.
 #[derive(Debug, Clone)]
 pub struct Artifacts {
     /// Generated atlas textures containing unique pixel content of the diced sprites.
     pub atlases: Vec<Texture>,
+    /// Mip chains of the generated atlas textures, mapped by atlas index. Each chain contains
+    /// [Prefs::mip_levels] textures, starting with the half-sized one.
+    pub mips: Vec<Vec<Texture>>,
     /// Generated diced sprites with data to reconstruct source spites: mesh, uvs, etc.
     pub sprites: Vec<DicedSprite>,
+    /// Unit cells placed on the generated atlases, mapped by atlas index. Each cell lists all
+    /// the diced sprites referencing its content; use to find sprites affected by a change.
+    pub cells: Vec<Vec<AtlasCell>>,
+    /// Statistics of the dicing operation; only collected when [Prefs::collect_stats] is enabled.
+    pub stats: Option<Stats>,
+}
+
+impl Artifacts {
+    /// Returns the sprite with mesh of the specified sprite: the sprite referenced by
+    /// [DicedSprite::alias_of] when the sprite is an alias, or the sprite itself otherwise.
+    pub fn resolve<'a>(&'a self, sprite: &'a DicedSprite) -> &'a DicedSprite {
+        let Some(id) = &sprite.alias_of else {
+            return sprite;
+        };
+        self.sprites.iter().find(|s| &s.id == id).unwrap_or(sprite)
+    }
+}
+
+/// Products of an incremental dicing operation.
+#[derive(Debug, Clone)]
+pub struct Incremental {
+    /// Products of the dicing operation, with the units of the previous atlases kept in place.
+    pub artifacts: Artifacts,
+    /// IDs of the diced sprites which are new or differ from the previous operation.
+    pub changed_sprites: Vec<String>,
+    /// IDs of the previously diced sprites missing in the operation.
+    pub removed_sprites: Vec<String>,
+    /// Indices of the atlas textures which are new or differ from the previous operation.
+    pub changed_atlases: Vec<usize>,
+}
+
+/// A diced unit placed on an atlas texture.
+#[derive(Debug, Clone)]
+pub struct AtlasCell {
+    /// Content hash of the unit; 64-bit FNV-1a over the RGBA bytes of the non-padded unit pixels,
+    /// stable across versions and platforms.
+    pub hash: u64,
+    /// Position and dimensions of the unit content on the atlas texture, excluding padding.
+    pub rect: URect,
+    /// Diced sprite units referencing the cell content.
+    pub refs: Vec<CellRef>,
+}
+
+/// Reference of a diced sprite to an [AtlasCell].
+#[derive(Debug, Clone)]
+pub struct CellRef {
+    /// ID of the source sprite referencing the cell.
+    pub sprite_id: String,
+    /// Position and dimensions of the referencing unit inside the source texture, in pixels.
+    /// May be smaller than the cell rect when the unit is cropped at the texture edges.
+    pub rect: URect,
+}
+
+/// Statistics of a dicing operation.
+#[derive(Debug, Clone)]
+pub struct Stats {
+    /// Statistics of the diced sprites, in the same order as [Artifacts::sprites].
+    pub sprites: Vec<SpriteStats>,
+    /// Statistics of the generated atlases, in the same order as [Artifacts::atlases].
+    pub atlases: Vec<AtlasStats>,
+    /// Total size of the source textures (uncompressed RGBA8), in bytes.
+    pub source_bytes: usize,
+    /// Total size of the atlas textures and their mips (uncompressed RGBA8), in bytes.
+    pub atlas_bytes: usize,
+    /// Total size of the diced sprite meshes (vertices, uvs and indices), in bytes.
+    pub mesh_bytes: usize,
+}
+
+impl Stats {
+    /// Size of the source textures divided by size of the atlases plus meshes (higher is better).
+    pub fn compression_ratio(&self) -> f32 {
+        self.source_bytes as f32 / (self.atlas_bytes + self.mesh_bytes) as f32
+    }
+}
+
+/// Statistics of a [DicedSprite].
+#[derive(Debug, Clone)]
+pub struct SpriteStats {
+    /// ID of the associated source sprite.
+    pub id: String,
+    /// Number of non-transparent units diced from the source texture.
+    pub units: usize,
+    /// Number of units with distinct content among the sprite's units.
+    pub unique: usize,
+    /// Number of the distinct units, which content is reused by other sprites.
+    pub shared: usize,
+    /// Number of the mesh vertices.
+    pub vertices: usize,
+    /// Number of the mesh indices.
+    pub indices: usize,
+    /// Size of the mesh (vertices, uvs and indices), in bytes.
+    pub mesh_bytes: usize,
+}
+
+/// Statistics of a generated atlas texture.
+#[derive(Debug, Clone)]
+pub struct AtlasStats {
+    /// Dimensions of the atlas texture, in pixels.
+    pub size: USize,
+    /// Number of units with distinct content packed into the atlas.
+    pub units: usize,
+    /// Ratio of the texels occupied by the unit cells to the total atlas texels, in 0.0 to 1.0 range.
+    pub occupancy: f32,
+    /// Number of the atlas texels not occupied by the unit cells.
+    pub wasted_texels: usize,
+}
+
+/// Projected products of a dicing operation, evaluated without baking the atlas textures.
+#[derive(Debug, Clone)]
+pub struct Estimate {
+    /// Dimensions of the atlas textures to be generated, in pixels.
+    pub atlases: Vec<USize>,
+    /// Number of diced units with distinct content among all the source sprites.
+    pub unique_units: usize,
+    /// Total number of mesh vertices of all the diced sprites.
+    pub vertices: usize,
+    /// Total number of mesh indices of all the diced sprites.
+    pub indices: usize,
+    /// Projected size of the atlas textures (uncompressed RGBA8), in bytes.
+    pub atlas_bytes: usize,
+    /// Projected size of the diced sprite meshes (vertices, uvs and indices), in bytes.
+    pub mesh_bytes: usize,
+}
+
+/// Candidate preferences and cost model to search for the optimal [Prefs].
+#[derive(Debug, Clone)]
+pub struct Search {
+    /// Unit sizes to evaluate, in pixels.
+    pub unit_sizes: Vec<u32>,
+    /// Paddings to evaluate with each unit size, in pixels. When empty, will only evaluate
+    /// the padding specified in the [Prefs]. Combinations with padding above unit size are skipped.
+    pub paddings: Vec<u32>,
+    /// Cost model to score the evaluated candidates.
+    pub cost: CostModel,
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Self {
+            unit_sizes: vec![8, 16, 32, 64, 128],
+            paddings: vec![],
+            cost: CostModel::default(),
+        }
+    }
+}
+
+/// Weights to evaluate cost of a dicing [Estimate] with; lower cost is better.
+/// Cost is evaluated as the weighted sum of total atlas texels, vertices and atlases count.
+#[derive(Debug, Clone)]
+pub struct CostModel {
+    /// Cost of a single atlas texel.
+    pub texel_weight: f32,
+    /// Cost of a single mesh vertex.
+    pub vertex_weight: f32,
+    /// Cost of a single atlas texture; eg, to account for the draw calls.
+    pub atlas_weight: f32,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        // Approximates memory footprint in bytes: RGBA8 texel is 4 bytes, while vertex
+        // position and uv take 16 bytes plus 1.5 indices (6 per 4 vertices) of 8 bytes.
+        Self {
+            texel_weight: 4.0,
+            vertex_weight: 28.0,
+            atlas_weight: 0.0,
+        }
+    }
+}
+
+/// A set of preferences evaluated while searching for the optimal [Prefs].
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// Evaluated unit size, in pixels.
+    pub unit_size: u32,
+    /// Evaluated padding, in pixels.
+    pub padding: u32,
+    /// Cost of the candidate evaluated with the [CostModel]; lower is better.
+    pub cost: f32,
+    /// Projected dicing products of the candidate.
+    pub estimate: Estimate,
+}
+
+/// Result of searching for the optimal [Prefs].
+pub struct Optimized {
+    /// Specified preferences with unit size and padding of the lowest cost candidate.
+    pub prefs: Prefs,
+    /// All the evaluated candidates, in order of evaluation.
+    pub candidates: Vec<Candidate>,
+}
+
+/// Sampling conditions to analyze texture bleeding of the diced sprites under.
+#[derive(Debug, Clone)]
+pub struct BleedCheck {
+    /// Scales at which the diced sprites are rendered, relative to the source texture size.
+    /// Fractional scales cause the bilinear filter to sample adjacent texels on the unit edges.
+    pub scales: Vec<f32>,
+    /// Mip levels at which the atlas textures are sampled; 0 is the full-sized atlas.
+    /// When a level is not generated per [Prefs::mip_levels], it's built by downsampling the
+    /// atlas without cell alignment, similar to mips generated by game engines.
+    pub mip_levels: Vec<u32>,
+    /// Maximum difference of a color channel (0-255) not considered a seam.
+    pub tolerance: f32,
+    /// Maximum padding to evaluate when searching for the recommended padding, in pixels.
+    pub max_padding: u32,
+    /// Maximum UV inset to evaluate when searching for the recommended inset.
+    pub max_uv_inset: f32,
+    /// Increment of UV inset when searching for the recommended inset.
+    pub uv_inset_step: f32,
+}
+
+impl Default for BleedCheck {
+    fn default() -> Self {
+        Self {
+            scales: vec![0.75, 1.5],
+            mip_levels: vec![0],
+            tolerance: 2.0,
+            max_padding: 8,
+            max_uv_inset: 0.2,
+            uv_inset_step: 0.01,
+        }
+    }
+}
+
+/// Result of analyzing texture bleeding of the diced sprites.
+#[derive(Debug, Clone)]
+pub struct Bleed {
+    /// Seams of the diced sprites, in the same order as [Artifacts::sprites].
+    pub seams: Vec<Seam>,
+    /// Minimum padding removing the seams with the specified UV inset, if any.
+    pub padding: Option<u32>,
+    /// Minimum UV inset removing the seams with the specified padding, if any.
+    pub uv_inset: Option<f32>,
+}
+
+/// Texture bleeding error of a diced sprite sampled with bilinear filtering.
+#[derive(Debug, Clone)]
+pub struct Seam {
+    /// ID of the diced sprite.
+    pub id: String,
+    /// Maximum difference of a color channel (0-255) with the source texture on the unit edges.
+    pub max_error: f32,
+    /// Number of the rendered pixels on the unit edges with error above the tolerance.
+    pub pixels: usize,
+}
+
+/// Difference between a [SourceSprite] and the sprite reconstructed from the dicing [Artifacts].
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    /// ID of the mismatched source sprite.
+    pub id: String,
+    /// Number of the reconstructed pixels different from the source.
+    pub pixels: usize,
+    /// Position of the first (in row-major order) different pixel on horizontal axis.
+    pub x: u32,
+    /// Position of the first (in row-major order) different pixel on vertical axis.
+    pub y: u32,
 }
 
 /// Generated dicing product of a [SourceSprite] containing mesh data and reference to the
 /// associated atlas texture required to reconstruct and render sprite at runtime.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DicedSprite {
     /// ID of the source sprite based on which this sprite is generated.
     pub id: String,
     /// Index of atlas texture in [Artifacts] containing the unique pixels for this sprite.
+    /// When the sprite spans multiple atlases (see [atlas_indices]), the new atlas containing
+    /// the units missing on the base atlases, or the base atlas with most of the units when
+    /// none are missing.
     pub atlas_index: usize,
     /// Local position of the generated sprite mesh vertices.
     pub vertices: Vec<Vertex>,
     /// Atlas texture coordinates mapped to the [vertices] vector.
     pub uvs: Vec<Uv>,
-    /// Mesh face (triangle) indices to the [vertices] and [uvs] vectors.
+    /// Mesh face (triangle) indices to the [vertices] and [uvs] vectors; faces of the opaque
+    /// units go first, followed by the translucent ones.
     pub indices: Vec<usize>,
+    /// Range of the [indices] forming faces of the units with fully opaque pixels, which
+    /// can be rendered front-to-back with depth writes and without blending.
+    pub opaque_indices: Range<usize>,
+    /// Range of the [indices] forming faces of the units with (partially) transparent pixels,
+    /// which require blending; follows [opaque_indices].
+    pub translucent_indices: Range<usize>,
     /// Rect of the sprite in conventional units space, aka boundaries.
     pub rect: Rect,
-    /// Relative position of the sprite origin point on the generated mesh.
+    /// Relative position of the sprite origin point on the generated mesh; counted from
+    /// bottom-left corner when [Coordinates::y_axis] is [YAxis::Up].
     pub pivot: Pivot,
+    /// Dimensions of the source texture, in pixels.
+    pub source_size: USize,
+    /// Rect of the sprite inside the source texture, in pixels; equals the whole texture
+    /// unless [Prefs::trim_transparent] is enabled. Counted from the bottom-left corner of
+    /// the texture when [Coordinates::y_axis] is [YAxis::Up].
+    pub pixel_rect: URect,
+    /// Position of the sprite origin point, in pixels from the same corner of [pixel_rect]
+    /// the relative [pivot] is counted from.
+    pub pixel_pivot: Pivot,
+    /// Metadata of the source sprite, as specified in [SourceSprite::metadata].
+    pub metadata: Metadata,
+    /// 9-slice border of the source sprite, as specified in [SourceSprite::border].
+    pub border: Option<Border>,
+    /// Slice region of each mesh vertex when [border] is specified; empty otherwise.
+    pub slices: Vec<Slice>,
+    /// Closed polygons tracing the boundaries of the non-transparent pixels, in the same space
+    /// as [vertices]; usable as collision shapes. Outer boundaries follow [Coordinates::winding],
+    /// while the holes go the opposite way. Empty unless [Prefs::outline_tolerance] is specified.
+    pub outlines: Vec<Vec<Vertex>>,
+    /// Color of each mesh vertex to multiply the sampled atlas color by when [Prefs::dedupe_tints]
+    /// is enabled (white for the units which are not tinted copies); empty otherwise.
+    pub colors: Vec<Pixel>,
+    /// Index of the atlas texture in [Artifacts] sampled by each mesh vertex when the sprite
+    /// spans multiple atlases, ie some of its units are reused from the base atlases (see
+    /// [crate::dice_with_base]); empty otherwise, in which case all the vertices sample the
+    /// atlas at [atlas_index].
+    pub atlas_indices: Vec<usize>,
+    /// ID of the earlier sprite with identical mesh when the sprite is an alias (see
+    /// [Prefs::alias_duplicates]). The mesh vectors of an alias ([vertices], [uvs], [indices],
+    /// [slices], [outlines], [colors] and [atlas_indices]) are empty; use the mesh of the referenced sprite
+    /// (eg, via [Artifacts::resolve]), while the rest of the fields are valid as is.
+    pub alias_of: Option<String>,
+}
+
+/// GPU-ready mesh data of a [DicedSprite]: interleaved vertex buffer and index buffer
+/// of the smallest fitting width, both in little-endian byte order.
+#[derive(Debug, Clone)]
+pub struct MeshBuffers {
+    /// Size of a single vertex in the vertex buffer, in bytes: two f32 position components
+    /// followed by two UV components of [MeshBuffers::uv_format] and, when [has_colors],
+    /// four RGBA8 color components.
+    pub stride: usize,
+    /// Format of the UV components in the vertex buffer.
+    pub uv_format: UvFormat,
+    /// Whether the vertices carry RGBA8 color, ie [DicedSprite::colors] are not empty.
+    pub has_colors: bool,
+    /// Format of the indices in the index buffer.
+    pub index_format: IndexFormat,
+    pub(crate) vertices: Vec<u8>,
+    pub(crate) indices: Vec<u8>,
+}
+
+impl MeshBuffers {
+    /// Interleaved position, UV and color (if any) of the mesh vertices, ready for upload.
+    pub fn vertex_bytes(&self) -> &[u8] {
+        &self.vertices
+    }
+    /// Mesh face (triangle) indices, ready for upload.
+    pub fn index_bytes(&self) -> &[u8] {
+        &self.indices
+    }
+    /// Number of the vertices in the vertex buffer.
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len() / self.stride
+    }
+    /// Number of the indices in the index buffer.
+    pub fn index_count(&self) -> usize {
+        self.indices.len() / self.index_format.size()
+    }
+}
+
+/// Format of the UV components in [MeshBuffers].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum UvFormat {
+    /// 32-bit float.
+    #[default]
+    Float32,
+    /// 16-bit (half) float.
+    Float16,
+    /// 16-bit unsigned integer normalized to 0.0-1.0 range (UNORM16).
+    Unorm16,
+}
+
+impl UvFormat {
+    /// Size of a single UV component, in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            UvFormat::Float32 => 4,
+            UvFormat::Float16 | UvFormat::Unorm16 => 2,
+        }
+    }
+}
+
+/// Format of the indices in [MeshBuffers].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum IndexFormat {
+    /// 16-bit unsigned integer; used when all the vertices can be addressed.
+    Uint16,
+    /// 32-bit unsigned integer.
+    Uint32,
+}
+
+impl IndexFormat {
+    /// Size of a single index, in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            IndexFormat::Uint16 => 2,
+            IndexFormat::Uint32 => 4,
+        }
+    }
 }
 
 /// A rectangle in conventional units space.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Rect {
-    /// Position of the top-left corner of the rectangle on horizontal axis.
+    /// Position of the top-left (bottom-left when Y-axis is up) corner of the rectangle
+    /// on horizontal axis.
     pub x: f32,
-    /// Position of the top-left corner of the rectangle on vertical axis.
+    /// Position of the top-left (bottom-left when Y-axis is up) corner of the rectangle
+    /// on vertical axis.
     pub y: f32,
     /// Length of the rectangle over horizontal axis, starting from X.
     pub width: f32,
@@ -226,6 +991,42 @@ impl Pivot {
     }
 }
 
+/// Distances from the edges of a sprite texture to the 9-slice lines, in pixels; top and
+/// bottom are as seen with the sprite displayed upright, regardless of [Coordinates].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct Border {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
+impl Border {
+    pub fn new(left: u32, right: u32, top: u32, bottom: u32) -> Self {
+        Border {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+}
+
+/// Region of a 9-slice sprite, delimited by the [Border]. Corners keep their size when
+/// the sprite is resized, edges stretch along one axis and center along both.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Slice {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
 /// Represents position of a mesh vertex in a local space coordinated with conventional units.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Vertex {
@@ -265,10 +1066,27 @@ pub(crate) struct DicedTexture {
     pub size: USize,
     /// Pivot of the associated [SourceSprite], if any.
     pub pivot: Option<Pivot>,
+    /// Metadata of the associated [SourceSprite].
+    pub metadata: Metadata,
+    /// 9-slice border of the associated [SourceSprite], if any.
+    pub border: Option<Border>,
+    /// PPU override of the associated [SourceSprite], if any.
+    pub ppu: Option<f32>,
+    /// Trim override of the associated [SourceSprite], if any.
+    pub trim_transparent: Option<bool>,
+    /// Union of the unit rects of all the frames in the sequence of the associated
+    /// [SourceSprite], if any; trimmed meshes are bounded by it instead of the own units.
+    pub bounds: Option<URect>,
+    /// Traced alpha boundaries of the source texture, in pixels; empty when disabled.
+    pub outlines: Vec<Vec<(u32, u32)>>,
     /// Associated diced units.
     pub units: Vec<DicedUnit>,
-    /// Hashes of diced units with distinct content.
+    /// Hashes of diced units with distinct content, except the ones found on the read-only
+    /// base atlases ([base]), which are not packed again.
     pub unique: HashSet<u64>,
+    /// Hashes of the units found on the read-only base atlases mapped to the indices of the
+    /// atlases, when the texture doesn't fit a single base atlas; empty otherwise.
+    pub base: HashMap<u64, usize>,
 }
 
 /// A chunk diced from a source texture.
@@ -280,6 +1098,35 @@ pub(crate) struct DicedUnit {
     pub pixels: Vec<Pixel>,
     /// Content hash based on the non-padded pixels of the unit.
     pub hash: u64,
+    /// Area of the unit covered by the mesh.
+    pub crop: Crop,
+    /// Whether all the pixels in the covered area are fully opaque.
+    pub opaque: bool,
+    /// Color to multiply the content with [hash] by to get the unit content; white unless
+    /// the unit is a tinted copy of another unit (see [Prefs::dedupe_tints]).
+    pub tint: Pixel,
+}
+
+/// Area of a [DicedUnit] covered by the mesh, in pixels relative to the unit rect.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Crop {
+    /// Bounds of the covered area; the whole unit when not cropped.
+    pub rect: URect,
+    /// Lengths of the diagonal cuts at the top-left, bottom-left, bottom-right and top-right
+    /// corners of the [rect] (in order of the mesh vertices); 0 when the corner isn't cut.
+    pub cuts: [u32; 4],
+}
+
+impl Crop {
+    /// Number of the mesh vertices outlining the covered area.
+    pub fn vertices(&self) -> usize {
+        4 + self.cuts.iter().filter(|c| **c > 0).count()
+    }
+
+    /// Number of the mesh indices triangulating the covered area.
+    pub fn indices(&self) -> usize {
+        (self.vertices() - 2) * 3
+    }
 }
 
 /// Product of packing [DicedTexture]s.
@@ -287,15 +1134,76 @@ pub(crate) struct DicedUnit {
 pub(crate) struct Atlas {
     /// The atlas texture containing unique content of the packed diced textures.
     pub texture: Texture,
-    /// Packed unit UV rects on the atlas texture, mapped by unit hashes.
+    /// Mip chain of the atlas texture, starting with the half-sized one.
+    pub mips: Vec<Texture>,
+    /// Packed unit UV rects on the atlas texture, mapped by unit hashes. The rects cover
+    /// whole unit size and are expected to be cropped to the actual size of a diced unit.
     pub rects: HashMap<u64, FRect>,
+    /// Unit cells placed on the atlas texture, in placement order.
+    pub cells: Vec<AtlasCell>,
     /// Diced textures packed into this atlas.
     pub packed: Vec<DicedTexture>,
 }
 
+/// Layout of a diced unit inside the cell of an atlas texture.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Cell {
+    /// Width and height of the cell, in pixels.
+    pub size: u32,
+    /// Padding between top-left corner of the cell and the unit content, in pixels.
+    pub pad: u32,
+    /// Padding between the unit content and bottom-right corner of the cell, in pixels.
+    /// May differ from [pad] when the padding is grown to fill an aligned cell.
+    pub pad_end: u32,
+}
+
+impl Cell {
+    pub fn new(prefs: &Prefs) -> Self {
+        let unit = prefs.unit_size;
+        let align = lcm(prefs.block_alignment.max(1), 1 << prefs.mip_levels);
+        let size = (unit + prefs.padding * 2).div_ceil(align) * align;
+        if prefs.grow_padding {
+            let pad = (size - unit) / 2;
+            let pad_end = size - unit - pad;
+            Cell { size, pad, pad_end }
+        } else {
+            let pad = prefs.padding;
+            Cell {
+                size,
+                pad,
+                pad_end: pad,
+            }
+        }
+    }
+
+    /// Width and height of the padded unit content, in pixels.
+    pub fn padded_size(&self, unit_size: u32) -> u32 {
+        self.pad + unit_size + self.pad_end
+    }
+}
+
+fn lcm(a: u32, b: u32) -> u32 {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    a / x * b
+}
+
+/// Product of distributing [DicedTexture]s over an atlas, before the atlas texture is baked.
+#[derive(Debug, Clone)]
+pub(crate) struct Plan {
+    /// Dimensions of the atlas texture, in pixels.
+    pub size: USize,
+    /// Number of units with distinct content to bake into the atlas.
+    pub units: usize,
+    /// Diced textures to pack into the atlas.
+    pub packed: Vec<DicedTexture>,
+}
+
 /// A rectangle in unsigned integer space.
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub(crate) struct URect {
+pub struct URect {
     /// Position of the top-left corner of the rectangle on horizontal axis.
     pub x: u32,
     /// Position of the top-left corner of the rectangle on vertical axis.
@@ -307,7 +1215,6 @@ pub(crate) struct URect {
 }
 
 impl URect {
-    #[allow(dead_code)] // Used in tests.
     pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
         URect {
             x,
@@ -357,7 +1264,7 @@ impl FRect {
 
 /// Size of arbitrary entity in unsigned integer space.
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub(crate) struct USize {
+pub struct USize {
     /// Width of the entity.
     pub width: u32,
     /// Height of the entity.
@@ -382,4 +1289,28 @@ mod tests {
         assert_eq!(pixel.b(), 3);
         assert_eq!(pixel.a(), 4);
     }
+
+    #[test]
+    fn error_codes_are_stable() {
+        assert_eq!(
+            Error::PaddingTooLarge {
+                padding: 2,
+                unit_size: 1
+            }
+            .code(),
+            3
+        );
+        assert_eq!(Error::Cancelled.code(), 7);
+        assert_eq!(Error::EmptySearch.code(), 20);
+    }
+
+    #[test]
+    fn reporting_progress_errs_when_cancelled() {
+        let prefs = Prefs {
+            on_cancel: Some(Box::new(|| true)),
+            ..Prefs::default()
+        };
+        assert!(Progress::report(&prefs, 1, 0, 1, "").is_err_and(|e| e == Error::Cancelled));
+        assert!(Progress::report(&Prefs::default(), 1, 0, 1, "").is_ok());
+    }
 }
@@ -0,0 +1,250 @@
+use crate::models::*;
+use std::borrow::Cow;
+
+/// Searches for the unit size and padding yielding the lowest cost.
+pub(crate) fn optimize(
+    sprites: &[SourceSprite],
+    mut prefs: Prefs,
+    search: &Search,
+) -> Result<Optimized> {
+    if search.unit_sizes.is_empty() {
+        return Err(Error::EmptySearch);
+    }
+
+    let on_progress = prefs.on_progress.take();
+    let paddings = eval_paddings(&prefs, search);
+    let total = search.unit_sizes.len() * paddings.len();
+    let mut candidates = Vec::with_capacity(total);
+    let mut last_err = None;
+
+    for &unit_size in search.unit_sizes.iter() {
+        for &padding in paddings.iter() {
+            report(&on_progress, candidates.len(), total, unit_size, padding);
+            prefs.unit_size = unit_size;
+            prefs.padding = padding;
+            match crate::estimate(&clamp_paddings(sprites, padding), &prefs) {
+                Ok(estimate) => candidates.push(Candidate {
+                    unit_size,
+                    padding,
+                    cost: search.cost.eval(&estimate),
+                    estimate,
+                }),
+                Err(Error::Cancelled) => return Err(Error::Cancelled),
+                Err(err) => last_err = Some(err),
+            }
+        }
+    }
+
+    let best = candidates.iter().min_by(|a, b| a.cost.total_cmp(&b.cost));
+    let Some(best) = best else {
+        return Err(last_err.unwrap());
+    };
+    prefs.unit_size = best.unit_size;
+    prefs.padding = best.padding;
+    prefs.on_progress = on_progress;
+    Ok(Optimized { prefs, candidates })
+}
+
+/// Clamps per-sprite padding overrides to the specified padding, as the overrides can't exceed
+/// the padding in prefs; the sprites are borrowed as is when there is nothing to clamp.
+pub(crate) fn clamp_paddings(sprites: &[SourceSprite], padding: u32) -> Cow<'_, [SourceSprite]> {
+    if !sprites
+        .iter()
+        .any(|s| s.padding.is_some_and(|p| p > padding))
+    {
+        return Cow::Borrowed(sprites);
+    }
+    let clamp = |s: &SourceSprite| SourceSprite {
+        padding: s.padding.map(|p| p.min(padding)),
+        ..s.to_owned()
+    };
+    Cow::Owned(sprites.iter().map(clamp).collect())
+}
+
+fn eval_paddings(prefs: &Prefs, search: &Search) -> Vec<u32> {
+    if search.paddings.is_empty() {
+        vec![prefs.padding]
+    } else {
+        search.paddings.to_owned()
+    }
+}
+
+fn report(cb: &Option<ProgressCallback>, idx: usize, len: usize, size: u32, pad: u32) {
+    if let Some(cb) = cb {
+        let ratio = idx as f32 / len as f32;
+        let activity = format!("Evaluating unit size {size} with padding {pad}...");
+        cb(Progress { ratio, activity });
+    }
+}
+
+impl CostModel {
+    /// Evaluates cost of the specified dicing estimate; lower is better.
+    pub fn eval(&self, estimate: &Estimate) -> f32 {
+        let texels = estimate
+            .atlases
+            .iter()
+            .map(|a| a.width as f32 * a.height as f32);
+        texels.sum::<f32>() * self.texel_weight
+            + estimate.vertices as f32 * self.vertex_weight
+            + estimate.atlases.len() as f32 * self.atlas_weight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::*;
+    use crate::models::*;
+
+    #[test]
+    fn errs_when_no_unit_sizes() {
+        let search = Search {
+            unit_sizes: vec![],
+            ..Search::default()
+        };
+        assert!(optimize(vec![&RGB4X4], &search).is_err_and(|e| e == Error::EmptySearch));
+    }
+
+    #[test]
+    fn errs_when_cancelled() {
+        let prefs = Prefs {
+            on_cancel: Some(Box::new(|| true)),
+            ..Prefs::default()
+        };
+        assert!(optimize_with(vec![&RGB4X4], &Search::default(), prefs)
+            .is_err_and(|e| e == Error::Cancelled));
+    }
+
+    #[test]
+    fn errs_when_all_candidates_invalid() {
+        let search = Search {
+            unit_sizes: vec![1, 2],
+            paddings: vec![4],
+            ..Search::default()
+        };
+        assert!(optimize(vec![&RGB4X4], &search)
+            .is_err_and(|e| matches!(e, Error::PaddingTooLarge { .. })));
+    }
+
+    #[test]
+    fn evaluates_all_valid_candidates() {
+        let search = Search {
+            unit_sizes: vec![1, 2, 4],
+            paddings: vec![0, 2],
+            ..Search::default()
+        };
+        let optimized = optimize(vec![&RGB4X4], &search).unwrap();
+        let evaluated = optimized
+            .candidates
+            .iter()
+            .map(|c| (c.unit_size, c.padding));
+        assert_eq!(
+            evaluated.collect::<Vec<_>>(),
+            vec![(1, 0), (2, 0), (2, 2), (4, 0), (4, 2)]
+        );
+    }
+
+    #[test]
+    fn clamps_sprite_padding_to_candidates() {
+        let search = Search {
+            unit_sizes: vec![2, 4],
+            paddings: vec![0, 2],
+            ..Search::default()
+        };
+        let sprites = vec![SourceSprite {
+            padding: Some(2),
+            ..RGB4X4.sprite()
+        }];
+        let prefs = Prefs {
+            padding: 2,
+            ..Prefs::default()
+        };
+        let optimized = crate::optimizer::optimize(&sprites, prefs, &search).unwrap();
+        assert_eq!(optimized.candidates.len(), 4);
+    }
+
+    #[test]
+    fn uses_prefs_padding_when_paddings_not_specified() {
+        let search = Search {
+            unit_sizes: vec![1, 2],
+            paddings: vec![],
+            ..Search::default()
+        };
+        let optimized = optimize(vec![&RGB4X4], &search).unwrap();
+        assert!(optimized.candidates.iter().all(|c| c.padding == 0));
+    }
+
+    #[test]
+    fn picks_candidate_with_lowest_cost() {
+        let texels_only = Search {
+            unit_sizes: vec![1, 2, 4],
+            cost: CostModel {
+                texel_weight: 1.0,
+                vertex_weight: 0.0,
+                atlas_weight: 0.0,
+            },
+            ..Search::default()
+        };
+        assert_eq!(
+            optimize(vec![&RGB4X4], &texels_only)
+                .unwrap()
+                .prefs
+                .unit_size,
+            1
+        );
+
+        let vertices_only = Search {
+            unit_sizes: vec![1, 2, 4],
+            cost: CostModel {
+                texel_weight: 0.0,
+                vertex_weight: 1.0,
+                atlas_weight: 0.0,
+            },
+            ..Search::default()
+        };
+        assert_eq!(
+            optimize(vec![&RGB4X4], &vertices_only)
+                .unwrap()
+                .prefs
+                .unit_size,
+            4
+        );
+    }
+
+    #[test]
+    fn cost_is_weighted_sum() {
+        let estimate = Estimate {
+            atlases: vec![USize::new(2, 3), USize::new(1, 1)],
+            unique_units: 0,
+            vertices: 10,
+            indices: 15,
+            atlas_bytes: 0,
+            mesh_bytes: 0,
+        };
+        let cost = CostModel {
+            texel_weight: 2.0,
+            vertex_weight: 3.0,
+            atlas_weight: 100.0,
+        };
+        assert_eq!(cost.eval(&estimate), 7.0 * 2.0 + 10.0 * 3.0 + 2.0 * 100.0);
+    }
+
+    #[test]
+    fn reports_progress() {
+        let progress =
+            sample_progress(|p| drop(optimize_with(vec![&RGB4X4], &Search::default(), p)));
+        assert!(progress.activity.starts_with("Evaluating unit size"));
+    }
+
+    fn optimize(src: Vec<&dyn AnySource>, search: &Search) -> Result<Optimized> {
+        let prefs = Prefs {
+            padding: 0,
+            ..Prefs::default()
+        };
+        optimize_with(src, search, prefs)
+    }
+
+    fn optimize_with(src: Vec<&dyn AnySource>, search: &Search, prefs: Prefs) -> Result<Optimized> {
+        let sprites = src.into_iter().map(|s| s.sprite()).collect::<Vec<_>>();
+        crate::optimizer::optimize(&sprites, prefs, search)
+    }
+}
@@ -0,0 +1,270 @@
+use crate::models::*;
+use std::collections::{BTreeMap, HashSet};
+
+/// Point on the texture pixel grid (pixel corner), in pixels from top-left corner.
+type Point = (u32, u32);
+
+/// Traces boundaries between the non-transparent and transparent pixels of the specified
+/// texture and simplifies them with the specified tolerance (max deviation), in pixels.
+/// Outer boundaries go counter-clockwise and holes clockwise, as seen on screen; pixels
+/// touching only diagonally are outlined separately.
+pub(crate) fn trace(texture: &Texture, tolerance: f32) -> Vec<Vec<Point>> {
+    let edges = collect_edges(texture);
+    let mut used = HashSet::new();
+    let mut outlines = Vec::new();
+    for (from, ends) in edges.iter() {
+        for to in ends.iter() {
+            if used.contains(&(*from, *to)) {
+                continue;
+            }
+            let outline = follow(&edges, (*from, *to), &mut used);
+            let outline = simplify(&outline, tolerance);
+            if outline.len() > 2 {
+                outlines.push(outline);
+            }
+        }
+    }
+    outlines
+}
+
+/// Collects boundary edges of the non-transparent pixels mapped by the start point;
+/// the pixels are on the left side of the edges (as seen on screen).
+fn collect_edges(tex: &Texture) -> BTreeMap<Point, Vec<Point>> {
+    let opaque = |x: i64, y: i64| {
+        x >= 0
+            && y >= 0
+            && x < tex.width as i64
+            && y < tex.height as i64
+            && tex.pixels[(x + y * tex.width as i64) as usize].a() > 0
+    };
+    let mut edges = BTreeMap::<Point, Vec<Point>>::new();
+    let mut add = |from: Point, to: Point| edges.entry(from).or_default().push(to);
+    for y in 0..tex.height {
+        for x in 0..tex.width {
+            let (ix, iy) = (x as i64, y as i64);
+            if !opaque(ix, iy) {
+                continue;
+            }
+            if !opaque(ix - 1, iy) {
+                add((x, y), (x, y + 1));
+            }
+            if !opaque(ix, iy + 1) {
+                add((x, y + 1), (x + 1, y + 1));
+            }
+            if !opaque(ix + 1, iy) {
+                add((x + 1, y + 1), (x + 1, y));
+            }
+            if !opaque(ix, iy - 1) {
+                add((x + 1, y), (x, y));
+            }
+        }
+    }
+    edges
+}
+
+/// Follows the edges starting with the specified one until the loop is closed;
+/// returns corners of the loop, omitting the points in the middle of straight runs.
+fn follow(
+    edges: &BTreeMap<Point, Vec<Point>>,
+    first: (Point, Point),
+    used: &mut HashSet<(Point, Point)>,
+) -> Vec<Point> {
+    let mut corners = Vec::new();
+    let mut prev_dir = None;
+    let (mut from, mut to) = first;
+    loop {
+        used.insert((from, to));
+        let dir = direction(from, to);
+        if prev_dir != Some(dir) {
+            corners.push(from);
+        }
+        prev_dir = Some(dir);
+        from = to;
+        to = next(&edges[&from], from, dir);
+        if (from, to) == first {
+            break;
+        }
+    }
+    // The first edge may continue a straight run of the last one.
+    if prev_dir == Some(direction(first.0, first.1)) {
+        corners.remove(0);
+    }
+    corners
+}
+
+/// Picks the edge to follow from the specified point; on saddles (two pixels touching
+/// diagonally) turns left, towards the traced pixel, so that the diagonal pixels aren't joined.
+fn next(ends: &[Point], from: Point, dir: (i8, i8)) -> Point {
+    match ends {
+        [end] => *end,
+        _ => *ends
+            .iter()
+            .find(|end| direction(from, **end) == turn_left(dir))
+            .unwrap(),
+    }
+}
+
+fn direction(from: Point, to: Point) -> (i8, i8) {
+    (
+        (to.0 as i64 - from.0 as i64).signum() as i8,
+        (to.1 as i64 - from.1 as i64).signum() as i8,
+    )
+}
+
+/// Rotates the direction to the left, as seen on screen (with Y-axis pointing down).
+fn turn_left((x, y): (i8, i8)) -> (i8, i8) {
+    (y, -x)
+}
+
+/// Simplifies the closed outline with the Douglas-Peucker algorithm.
+fn simplify(outline: &[Point], tolerance: f32) -> Vec<Point> {
+    if outline.len() < 4 || tolerance <= 0.0 {
+        return outline.to_vec();
+    }
+    // Split the loop at the first point and the farthest from it, and simplify the halves.
+    let far = (1..outline.len())
+        .max_by_key(|idx| dist_sq(outline[0], outline[*idx]))
+        .unwrap();
+    let mut closed = outline.to_vec();
+    closed.push(outline[0]);
+    let mut keep = vec![false; closed.len()];
+    keep[0] = true;
+    keep[far] = true;
+    mark(&closed, 0, far, tolerance, &mut keep);
+    mark(&closed, far, outline.len(), tolerance, &mut keep);
+    outline
+        .iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|(p, _)| *p)
+        .collect()
+}
+
+fn mark(points: &[Point], from: usize, to: usize, tolerance: f32, keep: &mut [bool]) {
+    if to <= from + 1 {
+        return;
+    }
+    let (a, b) = (points[from], points[to]);
+    let (idx, dist) = (from + 1..to)
+        .map(|i| (i, deviation(points[i], a, b)))
+        .max_by(|x, y| x.1.total_cmp(&y.1))
+        .unwrap();
+    if dist > tolerance {
+        keep[idx] = true;
+        mark(points, from, idx, tolerance, keep);
+        mark(points, idx, to, tolerance, keep);
+    }
+}
+
+/// Distance from the point to the segment, in pixels.
+fn deviation(p: Point, a: Point, b: Point) -> f32 {
+    let (px, py) = (p.0 as f32, p.1 as f32);
+    let (ax, ay) = (a.0 as f32, a.1 as f32);
+    let (dx, dy) = (b.0 as f32 - ax, b.1 as f32 - ay);
+    let len_sq = dx * dx + dy * dy;
+    let t = match len_sq {
+        0.0 => 0.0,
+        _ => (((px - ax) * dx + (py - ay) * dy) / len_sq).clamp(0.0, 1.0),
+    };
+    let (cx, cy) = (ax + t * dx, ay + t * dy);
+    ((px - cx).powi(2) + (py - cy).powi(2)).sqrt()
+}
+
+fn dist_sq(a: Point, b: Point) -> u64 {
+    let dx = a.0 as i64 - b.0 as i64;
+    let dy = a.1 as i64 - b.1 as i64;
+    (dx * dx + dy * dy) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::*;
+    use crate::models::*;
+    use crate::outliner::trace;
+
+    #[test]
+    fn single_pixel_is_outlined_with_square() {
+        assert_eq!(
+            trace(&R1X1, 0.0),
+            vec![vec![(0, 0), (0, 1), (1, 1), (1, 0)]]
+        );
+    }
+
+    #[test]
+    fn transparent_pixels_are_not_outlined() {
+        assert_eq!(
+            trace(&TTTM, 0.0),
+            vec![vec![(1, 1), (1, 2), (2, 2), (2, 1)]]
+        );
+        assert!(trace(&TTTT, 0.0).is_empty());
+    }
+
+    #[test]
+    fn straight_runs_are_merged() {
+        assert_eq!(
+            trace(&RGB4X4, 0.0),
+            vec![vec![(0, 0), (0, 4), (4, 4), (4, 0)]]
+        );
+    }
+
+    #[test]
+    fn concave_outline_is_traced() {
+        assert_eq!(
+            trace(&BTGT, 0.0),
+            vec![vec![(0, 0), (0, 2), (1, 2), (1, 0)]]
+        );
+        assert_eq!(
+            trace(&BGRT, 0.0),
+            vec![vec![(0, 0), (0, 2), (1, 2), (1, 1), (2, 1), (2, 0)]]
+        );
+    }
+
+    #[test]
+    fn diagonal_pixels_are_outlined_separately() {
+        assert_eq!(
+            trace(&tex(2, 2, vec![R, T, T, R]), 0.0),
+            vec![
+                vec![(0, 0), (0, 1), (1, 1), (1, 0)],
+                vec![(1, 1), (1, 2), (2, 2), (2, 1)]
+            ]
+        );
+    }
+
+    #[test]
+    fn holes_are_outlined_clockwise() {
+        #[rustfmt::skip]
+        let ring = tex(3, 3, vec![
+            R, R, R,
+            R, T, R,
+            R, R, R,
+        ]);
+        assert_eq!(
+            trace(&ring, 0.0),
+            vec![
+                vec![(0, 0), (0, 3), (3, 3), (3, 0)],
+                vec![(1, 1), (2, 1), (2, 2), (1, 2)]
+            ]
+        );
+    }
+
+    #[test]
+    fn staircase_is_simplified_within_tolerance() {
+        #[rustfmt::skip]
+        let stairs = tex(4, 4, vec![
+            R, T, T, T,
+            R, R, T, T,
+            R, R, R, T,
+            R, R, R, R,
+        ]);
+        assert_eq!(trace(&stairs, 0.0)[0].len(), 10);
+        assert_eq!(trace(&stairs, 1.0)[0], vec![(0, 0), (0, 4), (4, 4)]);
+    }
+
+    fn tex(width: u32, height: u32, pixels: Vec<Pixel>) -> Texture {
+        Texture {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
@@ -1,97 +1,150 @@
 use crate::models::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 /// Packs diced textures into atlases.
 pub(crate) fn pack(diced: Vec<DicedTexture>, prefs: &Prefs) -> Result<Vec<Atlas>> {
-    if prefs.uv_inset > 0.5 {
-        return Err(Error::Spec("UV inset should be in 0.0 to 0.5 range."));
+    let plans = plan(diced, prefs)?;
+    Ok(plans.into_iter().map(|p| bake(p, prefs)).collect())
+}
+
+/// Packs diced textures into the atlases of a previous dicing operation, keeping the units still
+/// in use in place, so that their UVs don't change. Missing units of a texture are placed into
+/// free cells of the atlas containing most of the texture units (or of any atlas with enough
+/// free cells); the textures which don't fit the previous atlases are packed into new atlases.
+/// When read-only, the previous atlases are kept intact: the textures with all the units found
+/// on a single previous atlas are packed into it, while the rest reference the units found on
+/// the previous atlases from there and have only the missing units packed into new atlases.
+pub(crate) fn repack(
+    diced: Vec<DicedTexture>,
+    prev: &Artifacts,
+    read_only: bool,
+    prefs: &Prefs,
+) -> Result<Vec<Atlas>> {
+    let cell = Cell::new(prefs);
+    let mut units = HashMap::new();
+    for unit in diced.iter().flat_map(|d| &d.units) {
+        units.entry(unit.hash).or_insert(unit);
+    }
+    let mut pages = Vec::with_capacity(prev.atlases.len());
+    for (idx, (texture, cells)) in prev.atlases.iter().zip(&prev.cells).enumerate() {
+        let prev = PrevAtlas {
+            index: idx,
+            texture,
+            cells,
+        };
+        pages.push(new_page(&prev, &units, read_only, &cell, prefs.unit_size)?);
+    }
+
+    let mut rest = vec![];
+    for mut tex in diced {
+        if let Some(idx) = pick_page(&pages, &tex) {
+            place(&mut pages[idx], tex);
+            continue;
+        }
+        if read_only {
+            split(&pages, &mut tex);
+        }
+        if !tex.unique.is_empty() {
+            rest.push(tex);
+            continue;
+        }
+        // All the units are found on the previous atlases, but spread over multiple ones.
+        let idx = most_used_page(&tex.base);
+        tex.base.retain(|_, page_idx| *page_idx != idx);
+        pages[idx].packed.push(tex);
+    }
+
+    let added = pack(rest, prefs)?;
+    let mut atlases = pages
+        .into_iter()
+        .map(|p| bake_page(p, prefs))
+        .collect::<Vec<_>>();
+    atlases.extend(added);
+    map_base_cells(&mut atlases);
+    Ok(atlases)
+}
+
+/// Distributes diced textures over atlases and evaluates atlas sizes, without baking the
+/// atlas textures.
+pub(crate) fn plan(diced: Vec<DicedTexture>, prefs: &Prefs) -> Result<Vec<Plan>> {
+    if !(0.0..=0.5).contains(&prefs.uv_inset) {
+        return Err(Error::InvalidUvInset {
+            uv_inset: prefs.uv_inset,
+        });
     }
     if prefs.atlas_size_limit == 0 {
-        return Err(Error::Spec("Atlas size limit can't be zero."));
+        return Err(Error::ZeroAtlasSizeLimit);
     }
     if prefs.unit_size > prefs.atlas_size_limit {
-        return Err(Error::Spec("Unit size can't be above atlas size limit."));
+        return Err(Error::InvalidUnitSize {
+            unit_size: prefs.unit_size,
+            atlas_size_limit: prefs.atlas_size_limit,
+        });
     }
 
     let total = diced.len();
-    let mut atlases = vec![];
+    let mut plans = vec![];
     let mut ctx = new_ctx(diced, prefs);
     while !ctx.to_pack.is_empty() {
-        Progress::report(prefs, 2, total - ctx.to_pack.len(), total, "Packing units");
-        atlases.push(pack_it(&mut ctx)?);
+        Progress::report(prefs, 2, total - ctx.to_pack.len(), total, "Packing units")?;
+        plans.push(plan_it(&mut ctx)?);
         ctx.packed.clear();
         ctx.units.clear();
     }
 
-    Ok(atlases)
+    Ok(plans)
 }
 
 struct Context {
-    inset: f32,
     square: bool,
     pot: bool,
     size_limit: u32,
-    unit_size: u32,
-    pad: u32,
-    padded_unit_size: u32,
+    cell: Cell,
     /// Max. number of units single atlas is able to accommodate.
     unit_capacity: u32,
     /// Total textures left to pack.
     to_pack: Vec<DicedTexture>,
     /// Indexes of to_pack textures packed into current atlas.
     packed: HashSet<usize>,
-    /// Units packed into current atlas mapped by hashes.
-    units: HashMap<u64, UnitRef>,
-}
-
-/// Reference to a diced unit of a diced texture.
-struct UnitRef {
-    /// Index of the diced texture (via ctx.to_pack) containing referenced unit.
-    tex_idx: usize,
-    /// Index of the referenced diced unit inside diced texture.
-    unit_idx: usize,
+    /// Hashes of the units packed into current atlas.
+    units: HashSet<u64>,
 }
 
 fn new_ctx(diced: Vec<DicedTexture>, prefs: &Prefs) -> Context {
-    let padded_unit_size = prefs.unit_size + prefs.padding * 2;
-    let unit_capacity = (prefs.atlas_size_limit / padded_unit_size).pow(2);
+    let cell = Cell::new(prefs);
+    let unit_capacity = (prefs.atlas_size_limit / cell.size).pow(2);
     Context {
-        inset: prefs.uv_inset,
         square: prefs.atlas_square,
         pot: prefs.atlas_pot,
         size_limit: prefs.atlas_size_limit,
-        unit_size: prefs.unit_size,
-        pad: prefs.padding,
-        padded_unit_size,
+        cell,
         unit_capacity,
         to_pack: diced,
         packed: HashSet::new(),
-        units: HashMap::new(),
+        units: HashSet::new(),
     }
 }
 
-fn pack_it(ctx: &mut Context) -> Result<Atlas> {
+fn plan_it(ctx: &mut Context) -> Result<Plan> {
     while let Some(tex_idx) = find_packable_texture(ctx) {
         ctx.packed.insert(tex_idx);
-        let units = ctx.to_pack[tex_idx].units.iter().enumerate();
-        let refs = units.map(|(unit_idx, u)| (u.hash, UnitRef { tex_idx, unit_idx }));
-        ctx.units.extend(refs);
+        ctx.units.extend(&ctx.to_pack[tex_idx].unique);
     }
 
     if ctx.packed.is_empty() {
-        return Err(Error::Spec(
-            "Can't fit single texture; increase atlas size limit.",
-        ));
+        // When nothing is packed, the texture with least unique units doesn't fit alone.
+        let texture = ctx.to_pack.iter().min_by_key(|t| t.unique.len()).unwrap();
+        return Err(Error::DoesntFit {
+            sprite_id: texture.id.to_owned(),
+            units: texture.unique.len(),
+            capacity: ctx.unit_capacity,
+        });
     }
 
-    let atlas_size = eval_atlas_size(ctx);
-    let (texture, rects) = bake_atlas(ctx, &atlas_size);
-    let packed = extract_packed_textures(ctx);
-
-    Ok(Atlas {
-        texture,
-        rects,
-        packed,
+    Ok(Plan {
+        size: eval_atlas_size(ctx),
+        units: ctx.units.len(),
+        packed: extract_packed_textures(ctx),
     })
 }
 
@@ -106,7 +159,7 @@ fn find_packable_texture(ctx: &Context) -> Option<usize> {
         let units_to_pack = texture
             .unique
             .iter()
-            .filter(|u| !ctx.units.contains_key(u))
+            .filter(|u| !ctx.units.contains(u))
             .count() as u32;
         if units_to_pack < min_units_to_pack {
             optimal_texture_idx = Some(idx);
@@ -127,19 +180,19 @@ fn eval_atlas_size(ctx: &Context) -> USize {
     let size = (units_count as f32).sqrt().ceil() as u32;
 
     if ctx.pot {
-        let size = (size * ctx.padded_unit_size).next_power_of_two();
+        let size = (size * ctx.cell.size).next_power_of_two();
         return USize::new(size, size);
     }
 
     if ctx.square {
-        let size = size * ctx.padded_unit_size;
+        let size = size * ctx.cell.size;
         return USize::new(size, size);
     }
 
     let mut size = USize::new(size, size);
     for width in (1..=size.width).rev() {
         let height = units_count.div_ceil(width);
-        if height * ctx.padded_unit_size > ctx.size_limit {
+        if height * ctx.cell.size > ctx.size_limit {
             break;
         }
         if width * height < size.width * size.height {
@@ -147,48 +200,396 @@ fn eval_atlas_size(ctx: &Context) -> USize {
         }
     }
 
-    USize::new(
-        size.width * ctx.padded_unit_size,
-        size.height * ctx.padded_unit_size,
-    )
+    USize::new(size.width * ctx.cell.size, size.height * ctx.cell.size)
+}
+
+fn extract_packed_textures(ctx: &mut Context) -> Vec<DicedTexture> {
+    let mut packed = Vec::new();
+    let mut idx = ctx.to_pack.len() - 1;
+    loop {
+        if ctx.packed.contains(&idx) {
+            packed.push(ctx.to_pack.swap_remove(idx));
+        }
+        if idx == 0 {
+            break;
+        }
+        idx -= 1;
+    }
+    packed
+}
+
+/// State of an atlas texture being baked.
+struct Baker<'a> {
+    inset: f32,
+    order: UnitOrder,
+    mip_levels: u32,
+    unit_size: u32,
+    cell: Cell,
+    /// Diced textures packed into the atlas.
+    packed: &'a [DicedTexture],
+    /// Units to bake into the atlas mapped by hashes.
+    units: HashMap<u64, UnitRef>,
+}
+
+/// Reference to a diced unit of a diced texture.
+struct UnitRef {
+    /// Index of the diced texture (via baker.packed) containing referenced unit.
+    tex_idx: usize,
+    /// Index of the referenced diced unit inside diced texture.
+    unit_idx: usize,
+}
+
+/// Atlas of a previous dicing operation being repacked.
+struct Page {
+    /// Texture of the previous atlas with the cells of the unused units cleared.
+    texture: Texture,
+    /// Grid positions (column and row) of the placed units available for reuse mapped by unit
+    /// hashes; a previous cell is available only when holding the content of the diced unit.
+    placed: HashMap<u64, (u32, u32)>,
+    /// Hashes and grid positions of the cells to bake, in order of placement; when read-only,
+    /// includes all the previous cells, whether available for reuse or not.
+    order: Vec<(u64, (u32, u32))>,
+    /// Hashes of the units placed when repacking, which are yet to be baked.
+    added: HashSet<u64>,
+    /// Grid positions (row and column) of the free cells, in row-major order.
+    free: BTreeSet<(u32, u32)>,
+    /// Diced textures packed into the atlas.
+    packed: Vec<DicedTexture>,
+}
+
+/// Atlas of a previous dicing operation along with its index and cells.
+struct PrevAtlas<'a> {
+    index: usize,
+    texture: &'a Texture,
+    cells: &'a [AtlasCell],
+}
+
+/// Creates page of the previous atlas, keeping the specified diced units in place and freeing
+/// the rest of the cells; all the cells are kept and none are freed when read-only.
+fn new_page(
+    prev: &PrevAtlas,
+    units: &HashMap<u64, &DicedUnit>,
+    read_only: bool,
+    cell: &Cell,
+    unit_size: u32,
+) -> Result<Page> {
+    let texture = prev.texture;
+    let (columns, rows) = (texture.width / cell.size, texture.height / cell.size);
+    let mut page = Page {
+        texture: texture.to_owned(),
+        placed: HashMap::with_capacity(prev.cells.len()),
+        order: Vec::with_capacity(prev.cells.len()),
+        added: HashSet::new(),
+        free: BTreeSet::new(),
+        packed: vec![],
+    };
+    for atlas_cell in prev.cells.iter() {
+        let located = locate(&atlas_cell.rect, cell, unit_size);
+        let Some((column, row)) = located.filter(|(c, r)| *c < columns && *r < rows) else {
+            return Err(Error::MismatchedCell {
+                atlas_index: prev.index,
+                rect: atlas_cell.rect.to_owned(),
+            });
+        };
+        // The content is verified in case the hash collides with a distinct diced unit.
+        let unit = units.get(&atlas_cell.hash);
+        let held = unit.is_some_and(|u| holds(texture, &atlas_cell.rect, u, cell));
+        if read_only {
+            if held {
+                page.placed.insert(atlas_cell.hash, (column, row));
+            }
+            page.order.push((atlas_cell.hash, (column, row)));
+        } else if held {
+            page.placed.insert(atlas_cell.hash, (column, row));
+            page.order.push((atlas_cell.hash, (column, row)));
+        } else {
+            clear_cell(&mut page.texture, column, row, cell);
+        }
+    }
+    if read_only {
+        return Ok(page);
+    }
+    let occupied = page.placed.values().collect::<HashSet<_>>();
+    for row in 0..rows {
+        for column in 0..columns {
+            if !occupied.contains(&(column, row)) {
+                page.free.insert((row, column));
+            }
+        }
+    }
+    Ok(page)
+}
+
+/// Whether the previous atlas texture holds content of the diced unit at the specified unit rect.
+fn holds(texture: &Texture, rect: &URect, unit: &DicedUnit, cell: &Cell) -> bool {
+    let stride = cell.padded_size(rect.width) as usize;
+    let width = rect.width as usize;
+    (0..rect.height as usize).all(|y| {
+        let from = (y + cell.pad as usize) * stride + cell.pad as usize;
+        let into = (rect.y as usize + y) * texture.width as usize + rect.x as usize;
+        unit.pixels[from..from + width] == texture.pixels[into..into + width]
+    })
+}
+
+/// Evaluates grid position (column and row) of the atlas cell with the specified unit rect.
+fn locate(rect: &URect, cell: &Cell, unit_size: u32) -> Option<(u32, u32)> {
+    let x = rect.x.checked_sub(cell.pad)?;
+    let y = rect.y.checked_sub(cell.pad)?;
+    let aligned = x % cell.size == 0 && y % cell.size == 0;
+    let sized = rect.width == unit_size && rect.height == unit_size;
+    (aligned && sized).then_some((x / cell.size, y / cell.size))
+}
+
+fn clear_cell(texture: &mut Texture, column: u32, row: u32, cell: &Cell) {
+    let (start_x, start_y) = (column * cell.size, row * cell.size);
+    for y in start_y..(start_y + cell.size) {
+        let start = (start_x + texture.width * y) as usize;
+        texture.pixels[start..start + cell.size as usize].fill(Pixel::default());
+    }
+}
+
+/// Picks the page containing most of the texture units among the pages with enough free cells
+/// for the rest of the units, if any; the earlier pages are preferred when equal.
+fn pick_page(pages: &[Page], tex: &DicedTexture) -> Option<usize> {
+    let mut best: Option<(usize, usize)> = None;
+    for (idx, page) in pages.iter().enumerate() {
+        let placed = tex.unique.iter().filter(|h| page.placed.contains_key(h));
+        let placed = placed.count();
+        if tex.unique.len() - placed > page.free.len() {
+            continue;
+        }
+        if best.is_none_or(|(_, most)| placed > most) {
+            best = Some((idx, placed));
+        }
+    }
+    best.map(|(idx, _)| idx)
+}
+
+/// Moves the units of the texture found on the read-only pages from the unique units to be
+/// packed into the base units referenced from the pages (the earlier pages are preferred).
+fn split(pages: &[Page], tex: &mut DicedTexture) {
+    for hash in tex.unique.iter() {
+        if let Some(idx) = pages.iter().position(|p| p.placed.contains_key(hash)) {
+            tex.base.insert(*hash, idx);
+        }
+    }
+    tex.unique.retain(|hash| !tex.base.contains_key(hash));
+}
+
+/// Resolves index of the page containing most of the base units; the earlier pages are
+/// preferred when equal.
+fn most_used_page(base: &HashMap<u64, usize>) -> usize {
+    let mut counts = BTreeMap::<usize, usize>::new();
+    for idx in base.values() {
+        *counts.entry(*idx).or_default() += 1;
+    }
+    let most = counts.iter().rev().max_by_key(|(_, count)| **count);
+    *most.unwrap().0
+}
+
+fn place(page: &mut Page, tex: DicedTexture) {
+    for unit in tex.units.iter() {
+        if page.placed.contains_key(&unit.hash) {
+            continue;
+        }
+        let (row, column) = page.free.pop_first().unwrap();
+        page.placed.insert(unit.hash, (column, row));
+        page.order.push((unit.hash, (column, row)));
+        page.added.insert(unit.hash);
+    }
+    page.packed.push(tex);
+}
+
+fn bake_page(page: Page, prefs: &Prefs) -> Atlas {
+    let baker = new_baker(&page.packed, page.placed.len(), prefs);
+    let mut texture = page.texture;
+    let size = USize::new(texture.width, texture.height);
+    let mut rects = HashMap::with_capacity(page.placed.len());
+    let mut cells = Vec::with_capacity(page.order.len());
+    for (hash, (column, row)) in page.order {
+        if page.added.contains(&hash) {
+            let unit_ref = &baker.units[&hash];
+            let unit = &baker.packed[unit_ref.tex_idx].units[unit_ref.unit_idx];
+            set_pixels(&baker, &unit.pixels, column, row, &mut texture);
+        }
+        let rect = get_uv(&baker, column, row, &size);
+        rects.insert(hash, inset_uv(&baker, rect));
+        cells.push(AtlasCell {
+            hash,
+            rect: get_cell_rect(&baker, column, row),
+            refs: vec![],
+        });
+    }
+    let mips = build_mips(&baker, &texture);
+    let cells = map_cells(&baker, cells);
+
+    Atlas {
+        texture,
+        mips,
+        rects,
+        cells,
+        packed: page.packed,
+    }
+}
+
+fn new_baker<'a>(packed: &'a [DicedTexture], units: usize, prefs: &Prefs) -> Baker<'a> {
+    let mut baker = Baker {
+        inset: prefs.uv_inset,
+        order: prefs.unit_order,
+        mip_levels: prefs.mip_levels,
+        unit_size: prefs.unit_size,
+        cell: Cell::new(prefs),
+        packed,
+        units: HashMap::with_capacity(units),
+    };
+    for (tex_idx, tex) in packed.iter().enumerate() {
+        let units = tex.units.iter().enumerate();
+        let units = units.filter(|(_, u)| !tex.base.contains_key(&u.hash));
+        let refs = units.map(|(unit_idx, u)| (u.hash, UnitRef { tex_idx, unit_idx }));
+        baker.units.extend(refs);
+    }
+    baker
+}
+
+fn bake(plan: Plan, prefs: &Prefs) -> Atlas {
+    let baker = new_baker(&plan.packed, plan.units, prefs);
+    let (texture, rects, cells) = bake_atlas(&baker, &plan.size);
+    let mips = build_mips(&baker, &texture);
+    let cells = map_cells(&baker, cells);
+
+    Atlas {
+        texture,
+        mips,
+        rects,
+        cells,
+        packed: plan.packed,
+    }
 }
 
-fn bake_atlas(ctx: &Context, size: &USize) -> (Texture, HashMap<u64, FRect>) {
-    let units_per_row = size.width / ctx.padded_unit_size;
+fn bake_atlas(ctx: &Baker, size: &USize) -> (Texture, HashMap<u64, FRect>, Vec<AtlasCell>) {
+    let units_per_row = size.width / ctx.cell.size;
     let mut rects = HashMap::new();
+    let mut cells = Vec::with_capacity(ctx.units.len());
     let mut texture = Texture {
         width: size.width,
         height: size.height,
         pixels: vec![Pixel::default(); (size.width * size.height) as usize],
     };
 
-    // Hash containers in Rust intentionally randomize order for security, while we need
-    // stable order to produce identical atlases for identical input, hence the sorting here.
-    let mut sorted_hashes = ctx.units.keys().collect::<Vec<_>>();
-    sorted_hashes.sort_unstable();
-
+    let sorted_hashes = sort_units(ctx);
     for (unit_idx, unit_hash) in sorted_hashes.into_iter().enumerate() {
-        let unit_ref = &ctx.units[unit_hash];
+        let unit_ref = &ctx.units[&unit_hash];
         let row = unit_idx as u32 / units_per_row;
         let column = unit_idx as u32 % units_per_row;
-        let unit = &ctx.to_pack[unit_ref.tex_idx].units[unit_ref.unit_idx];
+        let unit = &ctx.packed[unit_ref.tex_idx].units[unit_ref.unit_idx];
         set_pixels(ctx, &unit.pixels, column, row, &mut texture);
 
         let rect = get_uv(ctx, column, row, size);
         let rect = inset_uv(ctx, rect);
-        let rect = scale_uv(ctx, rect, unit);
-        rects.insert(*unit_hash, rect);
+        rects.insert(unit_hash, rect);
+        cells.push(AtlasCell {
+            hash: unit_hash,
+            rect: get_cell_rect(ctx, column, row),
+            refs: vec![],
+        });
+    }
+
+    (texture, rects, cells)
+}
+
+fn map_cells(ctx: &Baker, mut cells: Vec<AtlasCell>) -> Vec<AtlasCell> {
+    let indices = cells.iter().enumerate().map(|(idx, c)| (c.hash, idx));
+    let indices = indices.collect::<HashMap<_, _>>();
+    for tex in ctx.packed.iter() {
+        for unit in tex.units.iter() {
+            if tex.base.contains_key(&unit.hash) {
+                continue;
+            }
+            cells[indices[&unit.hash]].refs.push(CellRef {
+                sprite_id: tex.id.to_owned(),
+                rect: unit.rect.to_owned(),
+            });
+        }
     }
+    cells
+}
 
-    (texture, rects)
+/// Adds references to the cells of the base units from the textures packed into other atlases.
+fn map_base_cells(atlases: &mut [Atlas]) {
+    let mut refs = vec![];
+    for tex in atlases.iter().flat_map(|a| &a.packed) {
+        for unit in tex.units.iter() {
+            if let Some(idx) = tex.base.get(&unit.hash) {
+                let cell_ref = CellRef {
+                    sprite_id: tex.id.to_owned(),
+                    rect: unit.rect.to_owned(),
+                };
+                refs.push((*idx, unit.hash, cell_ref));
+            }
+        }
+    }
+    let mut indices = HashMap::<usize, HashMap<u64, usize>>::new();
+    for (idx, hash, cell_ref) in refs {
+        let cells = &mut atlases[idx].cells;
+        let indices = indices.entry(idx).or_insert_with(|| {
+            let indices = cells.iter().enumerate().map(|(idx, c)| (c.hash, idx));
+            indices.collect()
+        });
+        cells[indices[&hash]].refs.push(cell_ref);
+    }
 }
 
-fn set_pixels(ctx: &Context, pixels: &[Pixel], column: u32, row: u32, atlas: &mut Texture) {
+fn sort_units(ctx: &Baker) -> Vec<u64> {
+    // Hash containers in Rust intentionally randomize order for security, while we need
+    // stable order to produce identical atlases for identical input, hence the sorting here.
+    let mut hashes = ctx.units.keys().copied().collect::<Vec<_>>();
+    hashes.sort_unstable();
+    match ctx.order {
+        UnitOrder::Hash => (),
+        UnitOrder::Source => hashes.sort_by_cached_key(|h| {
+            let unit_ref = &ctx.units[h];
+            let tex = &ctx.packed[unit_ref.tex_idx];
+            let rect = &tex.units[unit_ref.unit_idx].rect;
+            (&tex.id, rect.y, rect.x)
+        }),
+        UnitOrder::Color => hashes.sort_by_cached_key(|h| {
+            let unit_ref = &ctx.units[h];
+            let unit = &ctx.packed[unit_ref.tex_idx].units[unit_ref.unit_idx];
+            interleave(average(&unit.pixels))
+        }),
+    }
+    hashes
+}
+
+fn average(pixels: &[Pixel]) -> [u8; 4] {
+    let mut sum = [0; 4];
+    for pixel in pixels {
+        for (idx, channel) in pixel.to_raw().into_iter().enumerate() {
+            sum[idx] += channel as usize;
+        }
+    }
+    sum.map(|s| (s / pixels.len()) as u8)
+}
+
+/// Interleaves bits of the color channels (Z-order curve), so that the colors
+/// close to each other in RGBA space are also close in the sort order.
+fn interleave(color: [u8; 4]) -> u32 {
+    let mut code = 0;
+    for bit in (0..8).rev() {
+        for channel in color {
+            code = (code << 1) | ((channel >> bit) & 1) as u32;
+        }
+    }
+    code
+}
+
+fn set_pixels(ctx: &Baker, pixels: &[Pixel], column: u32, row: u32, atlas: &mut Texture) {
     let mut from_idx = 0;
-    let start_x = column * ctx.padded_unit_size;
-    let start_y = row * ctx.padded_unit_size;
-    for y in start_y..(start_y + ctx.padded_unit_size) {
-        for x in start_x..(start_x + ctx.padded_unit_size) {
+    let size = ctx.cell.padded_size(ctx.unit_size);
+    let start_x = column * ctx.cell.size;
+    let start_y = row * ctx.cell.size;
+    for y in start_y..(start_y + size) {
+        for x in start_x..(start_x + size) {
             let into_idx = (x + atlas.width * y) as usize;
             atlas.pixels[into_idx] = pixels[from_idx];
             from_idx += 1;
@@ -196,39 +597,69 @@ fn set_pixels(ctx: &Context, pixels: &[Pixel], column: u32, row: u32, atlas: &mu
     }
 }
 
-fn get_uv(ctx: &Context, column: u32, row: u32, atlas_size: &USize) -> FRect {
+fn get_uv(ctx: &Baker, column: u32, row: u32, atlas_size: &USize) -> FRect {
     let width = ctx.unit_size as f32 / atlas_size.width as f32;
     let height = ctx.unit_size as f32 / atlas_size.height as f32;
-    let x = (column * ctx.padded_unit_size + ctx.pad) as f32 / atlas_size.width as f32;
-    let y = (row * ctx.padded_unit_size + ctx.pad) as f32 / atlas_size.height as f32;
+    let x = (column * ctx.cell.size + ctx.cell.pad) as f32 / atlas_size.width as f32;
+    let y = (row * ctx.cell.size + ctx.cell.pad) as f32 / atlas_size.height as f32;
     FRect::new(x, y, width, height)
 }
 
-fn inset_uv(ctx: &Context, rect: FRect) -> FRect {
+fn get_cell_rect(ctx: &Baker, column: u32, row: u32) -> URect {
+    let x = column * ctx.cell.size + ctx.cell.pad;
+    let y = row * ctx.cell.size + ctx.cell.pad;
+    URect::new(x, y, ctx.unit_size, ctx.unit_size)
+}
+
+fn inset_uv(ctx: &Baker, rect: FRect) -> FRect {
     let d = ctx.inset * (rect.width / 2.0);
     let dx2 = d * 2.0;
     FRect::new(rect.x + d, rect.y + d, rect.width - dx2, rect.height - dx2)
 }
 
-fn scale_uv(ctx: &Context, rect: FRect, unit: &DicedUnit) -> FRect {
-    let mx = unit.rect.width as f32 / ctx.unit_size as f32;
-    let my = unit.rect.height as f32 / ctx.unit_size as f32;
-    FRect::new(rect.x, rect.y, rect.width * mx, rect.height * my)
+fn build_mips(ctx: &Baker, texture: &Texture) -> Vec<Texture> {
+    let mut mips: Vec<Texture> = Vec::with_capacity(ctx.mip_levels as usize);
+    for _ in 0..ctx.mip_levels {
+        mips.push(downsample(mips.last().unwrap_or(texture)));
+    }
+    mips
 }
 
-fn extract_packed_textures(ctx: &mut Context) -> Vec<DicedTexture> {
-    let mut packed = Vec::new();
-    let mut idx = ctx.to_pack.len() - 1;
-    loop {
-        if ctx.packed.contains(&idx) {
-            packed.push(ctx.to_pack.swap_remove(idx));
+pub(crate) fn downsample(tex: &Texture) -> Texture {
+    // Cells are aligned to power of two boundaries, so each 2x2 block of the texture
+    // always belongs to a single cell and the units are downsampled independently.
+    let width = tex.width / 2;
+    let height = tex.height / 2;
+    let mut pixels = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let block = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                .map(|(dx, dy)| tex.pixels[(x * 2 + dx + tex.width * (y * 2 + dy)) as usize]);
+            pixels.push(average_block(&block));
         }
-        if idx == 0 {
-            break;
-        }
-        idx -= 1;
     }
-    packed
+    Texture {
+        width,
+        height,
+        pixels,
+    }
+}
+
+fn average_block(block: &[Pixel; 4]) -> Pixel {
+    // Color is weighted by alpha to prevent transparent pixels from darkening the result.
+    let alpha = block.iter().map(|p| p.a() as u32).sum::<u32>();
+    if alpha == 0 {
+        return Pixel::default();
+    }
+    let channel = |c: fn(&Pixel) -> u8| {
+        let sum = block
+            .iter()
+            .map(|p| c(p) as u32 * p.a() as u32)
+            .sum::<u32>();
+        ((sum + alpha / 2) / alpha) as u8
+    };
+    let a = ((alpha + 2) / 4) as u8;
+    Pixel::new(channel(Pixel::r), channel(Pixel::g), channel(Pixel::b), a)
 }
 
 #[cfg(test)]
@@ -242,7 +673,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "UV inset should be in 0.0 to 0.5 range.")]
+    #[should_panic(expected = "InvalidUvInset { uv_inset: 0.85 }")]
     fn errs_when_inset_above_05() {
         let prefs = Prefs {
             uv_inset: 0.85,
@@ -252,7 +683,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Atlas size limit can't be zero.")]
+    #[should_panic(expected = "ZeroAtlasSizeLimit")]
     fn errs_when_limit_is_zero() {
         let prefs = Prefs {
             atlas_size_limit: 0,
@@ -262,7 +693,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Unit size can't be above atlas size limit.")]
+    #[should_panic(expected = "InvalidUnitSize { unit_size: 2, atlas_size_limit: 1 }")]
     fn errs_when_unit_size_above_limit() {
         let prefs = Prefs {
             unit_size: 2,
@@ -287,7 +718,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Can't fit single texture; increase atlas size limit.")]
+    #[should_panic(expected = r#"DoesntFit { sprite_id: "TEST", units: 3, capacity: 1 }"#)]
     fn errs_when_content_from_single_texture_doesnt_fit() {
         let prefs = Prefs {
             atlas_size_limit: 1,
@@ -373,7 +804,8 @@ mod tests {
     }
 
     #[test]
-    fn overflow_uvs_are_cropped() {
+    fn overflow_uvs_are_not_cropped() {
+        // Units with equal content may be cropped differently, hence it's done by builder.
         let prefs = Prefs {
             unit_size: 2,
             padding: 1,
@@ -381,7 +813,183 @@ mod tests {
         };
         let atlas = pack(vec![&M1X1], &prefs).pop().unwrap();
         let rect = atlas.rects.values().next().unwrap();
+        assert_eq!(*rect, FRect::new(0.25, 0.25, 0.5, 0.5));
+    }
+
+    #[test]
+    fn hash_order_is_stable() {
+        let atlas1 = pack(vec![&RGB4X4, &PLT4X4], &defaults()).pop().unwrap();
+        let atlas2 = pack(vec![&RGB4X4, &PLT4X4], &defaults()).pop().unwrap();
+        assert_eq!(atlas1.texture.pixels, atlas2.texture.pixels);
+    }
+
+    #[test]
+    fn source_order_follows_source_positions() {
+        let prefs = Prefs {
+            unit_order: UnitOrder::Source,
+            ..defaults()
+        };
+        let atlas = pack(vec![&RGBY], &prefs).pop().unwrap();
+        assert_eq!(atlas.texture.pixels, vec![R, G, B, Y]);
+    }
+
+    #[test]
+    fn color_order_groups_similar_colors() {
+        let prefs = Prefs {
+            unit_order: UnitOrder::Color,
+            ..defaults()
+        };
+        let atlas = pack(vec![&RGBY], &prefs).pop().unwrap();
+        assert_eq!(atlas.texture.pixels, vec![B, G, R, Y]);
+    }
+
+    #[test]
+    fn cells_are_aligned_to_blocks() {
+        let prefs = Prefs {
+            block_alignment: 4,
+            ..defaults()
+        };
+        let atlas = pack(vec![&R1X1, &B1X1], &prefs).pop().unwrap();
+        assert_eq!(atlas.texture.width, 8);
+        assert_eq!(atlas.texture.height, 4);
+    }
+
+    #[test]
+    fn aligned_uvs_are_mapped() {
+        let prefs = Prefs {
+            block_alignment: 4,
+            ..defaults()
+        };
+        let atlas = pack(vec![&R1X1], &prefs).pop().unwrap();
+        let rect = atlas.rects.values().next().unwrap();
+        assert_eq!(*rect, FRect::new(0.0, 0.0, 0.25, 0.25));
+    }
+
+    #[test]
+    fn when_growing_padding_uvs_are_offset() {
+        let prefs = Prefs {
+            block_alignment: 4,
+            grow_padding: true,
+            ..defaults()
+        };
+        let atlas = pack(vec![&R1X1], &prefs).pop().unwrap();
+        let rect = atlas.rects.values().next().unwrap();
         assert_eq!(*rect, FRect::new(0.25, 0.25, 0.25, 0.25));
+        assert!(atlas.texture.pixels.iter().all(|p| *p == R));
+    }
+
+    #[test]
+    fn when_not_growing_padding_extra_space_is_clear() {
+        let prefs = Prefs {
+            block_alignment: 4,
+            ..defaults()
+        };
+        let atlas = pack(vec![&R1X1], &prefs).pop().unwrap();
+        assert_eq!(atlas.texture.pixels.iter().filter(|p| **p == R).count(), 1);
+    }
+
+    #[test]
+    fn cells_are_aligned_to_mip_levels() {
+        let prefs = Prefs {
+            mip_levels: 2,
+            ..defaults()
+        };
+        let atlas = pack(vec![&R1X1, &B1X1], &prefs).pop().unwrap();
+        assert_eq!(atlas.texture.width, 8);
+        assert_eq!(atlas.texture.height, 4);
+    }
+
+    #[test]
+    fn mips_are_generated() {
+        let prefs = Prefs {
+            mip_levels: 2,
+            ..defaults()
+        };
+        let atlas = pack(vec![&R1X1, &B1X1], &prefs).pop().unwrap();
+        assert_eq!(atlas.mips.len(), 2);
+        assert_eq!((atlas.mips[0].width, atlas.mips[0].height), (4, 2));
+        assert_eq!((atlas.mips[1].width, atlas.mips[1].height), (2, 1));
+    }
+
+    #[test]
+    fn mips_are_not_generated_by_default() {
+        let atlas = pack(vec![&R1X1], &defaults()).pop().unwrap();
+        assert!(atlas.mips.is_empty());
+    }
+
+    #[test]
+    fn mips_dont_blend_adjacent_units() {
+        let prefs = Prefs {
+            mip_levels: 1,
+            grow_padding: true,
+            ..defaults()
+        };
+        let atlas = pack(vec![&R1X1, &B1X1], &prefs).pop().unwrap();
+        let mut pixels = atlas.mips[0].pixels.to_owned();
+        pixels.sort_unstable_by_key(|p| p.to_raw());
+        assert_eq!(pixels, vec![B, R]);
+    }
+
+    #[test]
+    fn mips_dont_darken_translucent_content() {
+        let prefs = Prefs {
+            mip_levels: 1,
+            ..defaults()
+        };
+        let atlas = pack(vec![&R1X1], &prefs).pop().unwrap();
+        assert_eq!(atlas.mips[0].pixels, vec![Pixel::new(255, 0, 0, 64)]);
+    }
+
+    #[test]
+    fn cells_are_placed_excluding_padding() {
+        let prefs = Prefs {
+            padding: 1,
+            ..defaults()
+        };
+        let atlas = pack(vec![&R1X1], &prefs).pop().unwrap();
+        assert_eq!(atlas.cells.len(), 1);
+        assert_eq!(atlas.cells[0].rect, URect::new(1, 1, 1, 1));
+        assert!(atlas.rects.contains_key(&atlas.cells[0].hash));
+    }
+
+    #[test]
+    fn cells_reference_all_units() {
+        let atlas = pack(vec![&RGB4X4], &defaults()).pop().unwrap();
+        assert_eq!(atlas.cells.len(), 3);
+        assert_eq!(atlas.cells.iter().map(|c| c.refs.len()).sum::<usize>(), 16);
+        for cell in atlas.cells {
+            let atlas_idx = cell.rect.x + cell.rect.y * atlas.texture.width;
+            for cell_ref in cell.refs {
+                let src_idx = cell_ref.rect.x + cell_ref.rect.y * RGB4X4.width;
+                assert_eq!(
+                    atlas.texture.pixels[atlas_idx as usize],
+                    RGB4X4.pixels[src_idx as usize]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn shared_cells_reference_each_sprite() {
+        let atlas = pack(vec![&RGBY, &R1X1], &defaults()).pop().unwrap();
+        let red = atlas.cells.iter().find(|c| c.refs.len() > 1).unwrap();
+        assert_eq!(red.refs.len(), 2);
+        assert_eq!(red.refs[0].rect, URect::new(0, 0, 1, 1));
+        assert_eq!(red.refs[1].rect, URect::new(0, 0, 1, 1));
+    }
+
+    #[test]
+    fn cell_refs_preserve_cropped_rects() {
+        let prefs = Prefs {
+            unit_size: 2,
+            ..defaults()
+        };
+        let atlas = pack(vec![&RGB1X3], &prefs).pop().unwrap();
+        let rects = atlas.cells.iter().flat_map(|c| &c.refs).map(|r| &r.rect);
+        let mut rects = rects.cloned().collect::<Vec<_>>();
+        rects.sort_by_key(|r| r.y);
+        assert_eq!(rects, vec![URect::new(0, 0, 1, 2), URect::new(0, 2, 1, 1)]);
+        assert!(atlas.cells.iter().all(|c| c.rect.width == 2));
     }
 
     #[test]
@@ -390,6 +998,152 @@ mod tests {
         assert_eq!(progress.ratio, 0.6);
     }
 
+    #[test]
+    fn repacked_units_keep_placement() {
+        let prev = crate::dice(&[RGB4X4.sprite()], &defaults()).unwrap();
+        let atlases = repack(&prev, vec![&RGB4X4], &defaults());
+        assert_eq!(atlases.len(), 1);
+        assert_eq!(atlases[0].texture, prev.atlases[0]);
+        for cell in prev.cells[0].iter() {
+            let repacked = atlases[0]
+                .cells
+                .iter()
+                .find(|c| c.hash == cell.hash)
+                .unwrap();
+            assert_eq!(repacked.rect, cell.rect);
+        }
+    }
+
+    #[test]
+    fn new_units_are_placed_into_free_cells() {
+        let prefs = Prefs {
+            atlas_square: true,
+            ..defaults()
+        };
+        let prev = crate::dice(&[RGB4X4.sprite()], &prefs).unwrap();
+        let atlases = repack(&prev, vec![&RGB4X4, &Y1X1], &prefs);
+        assert_eq!(atlases.len(), 1);
+        assert_eq!(atlases[0].texture.pixels[..3], prev.atlases[0].pixels[..3]);
+        assert_eq!(atlases[0].texture.pixels[3], Y);
+        assert_eq!(
+            atlases[0].cells.last().unwrap().rect,
+            URect::new(1, 1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn cells_of_unused_units_are_reused() {
+        let prev = crate::dice(&[RGBY.sprite()], &defaults()).unwrap();
+        let atlases = repack(&prev, vec![&C1X1, &R1X1], &defaults());
+        assert_eq!(atlases.len(), 1);
+        let pixels = &atlases[0].texture.pixels;
+        assert_eq!(pixels.iter().filter(|p| **p == T).count(), 2);
+        let red = prev.atlases[0].pixels.iter().position(|p| *p == R);
+        assert_eq!(pixels.iter().position(|p| *p == R), red);
+        assert!(pixels.contains(&C));
+    }
+
+    #[test]
+    fn cells_with_mismatched_content_are_replaced() {
+        let mut prev = crate::dice(&[RGBY.sprite()], &defaults()).unwrap();
+        let red = prev.atlases[0].pixels.iter().position(|p| *p == R).unwrap();
+        prev.atlases[0].pixels[red] = C;
+        let atlases = repack(&prev, vec![&RGBY], &defaults());
+        assert_eq!(atlases.len(), 1);
+        assert!(!atlases[0].texture.pixels.contains(&C));
+        assert!(atlases[0].texture.pixels.contains(&R));
+    }
+
+    #[test]
+    fn textures_not_fitting_previous_atlases_are_packed_into_new_atlases() {
+        let prev = crate::dice(&[R1X1.sprite()], &defaults()).unwrap();
+        let atlases = repack(&prev, vec![&R1X1, &RGBY], &defaults());
+        assert_eq!(atlases.len(), 2);
+        assert_eq!(atlases[0].texture, prev.atlases[0]);
+        assert_eq!(atlases[1].packed[0].unique.len(), 4);
+    }
+
+    #[test]
+    fn errs_when_previous_cells_mismatch_prefs() {
+        let prev = crate::dice(&[RGB4X4.sprite()], &defaults()).unwrap();
+        let prefs = Prefs {
+            padding: 1,
+            ..defaults()
+        };
+        let diced = crate::dicer::dice(&[RGB4X4.sprite()], &prefs).unwrap();
+        assert!(crate::packer::repack(diced, &prev, false, &prefs)
+            .is_err_and(|e| matches!(e, Error::MismatchedCell { atlas_index: 0, .. })));
+    }
+
+    #[test]
+    fn read_only_atlases_are_intact() {
+        let prefs = Prefs {
+            atlas_square: true,
+            ..defaults()
+        };
+        let base = crate::dice(&[RGBY.sprite()], &prefs).unwrap();
+        let sprites = [R1X1.sprite(), C1X1.sprite()];
+        let diced = crate::dicer::dice(&sprites, &prefs).unwrap();
+        let atlases = crate::packer::repack(diced, &base, true, &prefs).unwrap();
+        assert_eq!(atlases.len(), 2);
+        assert_eq!(atlases[0].texture, base.atlases[0]);
+        assert_eq!(atlases[0].cells.len(), 4);
+        assert_eq!(atlases[0].packed.len(), 1);
+        assert_eq!(atlases[1].texture.pixels, vec![C]);
+    }
+
+    #[test]
+    fn only_units_missing_on_read_only_atlases_are_packed() {
+        let base = crate::dice(&[RGB1X3.sprite()], &defaults()).unwrap();
+        let diced = crate::dicer::dice(&[RGBY.sprite()], &defaults()).unwrap();
+        let atlases = crate::packer::repack(diced, &base, true, &defaults()).unwrap();
+        assert_eq!(atlases.len(), 2);
+        assert_eq!(atlases[0].texture, base.atlases[0]);
+        assert_eq!(atlases[1].texture.pixels, vec![Y]);
+        assert_eq!(atlases[1].packed[0].base.len(), 3);
+        assert!(atlases[0].cells.iter().all(|c| c.refs.len() == 1));
+    }
+
+    #[test]
+    fn read_only_cells_with_mismatched_content_are_not_reused() {
+        let mut base = crate::dice(&[RGBY.sprite()], &defaults()).unwrap();
+        let red = base.atlases[0].pixels.iter().position(|p| *p == R).unwrap();
+        base.atlases[0].pixels[red] = C;
+        let diced = crate::dicer::dice(&[R1X1.sprite()], &defaults()).unwrap();
+        let atlases = crate::packer::repack(diced, &base, true, &defaults()).unwrap();
+        assert_eq!(atlases.len(), 2);
+        assert_eq!(atlases[0].texture, base.atlases[0]);
+        assert_eq!(atlases[0].cells.len(), 4);
+        assert_eq!(atlases[1].texture.pixels, vec![R]);
+        assert!(atlases[1].packed[0].base.is_empty());
+    }
+
+    #[test]
+    fn units_spread_over_read_only_atlases_are_not_packed() {
+        let prefs = Prefs {
+            atlas_size_limit: 1,
+            ..defaults()
+        };
+        let mut sprites = [R1X1.sprite(), G1X1.sprite(), B1X1.sprite()];
+        for (idx, sprite) in sprites.iter_mut().enumerate() {
+            sprite.id = idx.to_string();
+        }
+        let base = crate::dice(&sprites, &prefs).unwrap();
+        let diced = crate::dicer::dice(&[RGB3X1.sprite()], &prefs).unwrap();
+        let atlases = crate::packer::repack(diced, &base, true, &prefs).unwrap();
+        assert_eq!(atlases.len(), 3);
+        assert_eq!(atlases[0].packed.len(), 1);
+        let mut indices = atlases[0].packed[0].base.values().collect::<Vec<_>>();
+        indices.sort();
+        assert_eq!(indices, [&1, &2]);
+    }
+
+    fn repack(prev: &Artifacts, src: Vec<&dyn AnySource>, prefs: &Prefs) -> Vec<Atlas> {
+        let sprites = src.into_iter().map(|s| s.sprite()).collect::<Vec<_>>();
+        let diced = crate::dicer::dice(&sprites, prefs).unwrap();
+        crate::packer::repack(diced, prev, false, prefs).unwrap()
+    }
+
     fn pack(src: Vec<&dyn AnySource>, prefs: &Prefs) -> Vec<Atlas> {
         let sprites = src.into_iter().map(|s| s.sprite()).collect::<Vec<_>>();
         let diced = crate::dicer::dice(&sprites, prefs).unwrap();
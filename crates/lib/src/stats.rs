@@ -0,0 +1,199 @@
+use crate::models::*;
+use std::collections::HashMap;
+
+/// Collects statistics of the packed atlases and sprites built from them.
+pub(crate) fn collect(
+    sources: &[SourceSprite],
+    packed: &[Atlas],
+    sprites: &[DicedSprite],
+    prefs: &Prefs,
+) -> Stats {
+    let diced = packed.iter().flat_map(|a| &a.packed).collect::<Vec<_>>();
+    let sprites = collect_sprites(&diced, sprites);
+    let cell = Cell::new(prefs);
+    let atlases = packed
+        .iter()
+        .map(|a| collect_atlas(a, &cell))
+        .collect::<Vec<_>>();
+    Stats {
+        source_bytes: sources.iter().map(|s| texture_bytes(&s.texture)).sum(),
+        atlas_bytes: packed
+            .iter()
+            .flat_map(|a| std::iter::once(&a.texture).chain(&a.mips))
+            .map(texture_bytes)
+            .sum(),
+        mesh_bytes: sprites.iter().map(|s| s.mesh_bytes).sum(),
+        sprites,
+        atlases,
+    }
+}
+
+/// Evaluates size of a mesh with the specified number of vertices and indices, in bytes.
+pub(crate) fn mesh_bytes(vertices: usize, indices: usize) -> usize {
+    vertices * (size_of::<Vertex>() + size_of::<Uv>()) + indices * size_of::<usize>()
+}
+
+fn collect_sprites(diced: &[&DicedTexture], sprites: &[DicedSprite]) -> Vec<SpriteStats> {
+    let mut users = HashMap::<u64, usize>::new();
+    for hash in diced.iter().flat_map(|d| distinct(d)) {
+        *users.entry(hash).or_default() += 1;
+    }
+    diced
+        .iter()
+        .zip(sprites)
+        .map(|(diced, sprite)| SpriteStats {
+            id: sprite.id.to_owned(),
+            units: diced.units.len(),
+            unique: diced.unique.len() + diced.base.len(),
+            shared: distinct(diced).filter(|h| users[h] > 1).count(),
+            vertices: sprite.vertices.len(),
+            indices: sprite.indices.len(),
+            mesh_bytes: mesh_bytes(sprite.vertices.len(), sprite.indices.len()),
+        })
+        .collect()
+}
+
+/// Hashes of the distinct units of the texture, including the ones found on the base atlases.
+fn distinct(diced: &DicedTexture) -> impl Iterator<Item = u64> + '_ {
+    diced.unique.iter().chain(diced.base.keys()).copied()
+}
+
+fn collect_atlas(atlas: &Atlas, cell: &Cell) -> AtlasStats {
+    let size = USize::new(atlas.texture.width, atlas.texture.height);
+    let texels = size.width as usize * size.height as usize;
+    let occupied = atlas.rects.len() * (cell.size * cell.size) as usize;
+    AtlasStats {
+        units: atlas.rects.len(),
+        occupancy: occupied as f32 / texels as f32,
+        wasted_texels: texels - occupied,
+        size,
+    }
+}
+
+fn texture_bytes(texture: &Texture) -> usize {
+    texture.width as usize * texture.height as usize * size_of::<Pixel>()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::*;
+    use crate::models::*;
+
+    #[test]
+    fn stats_not_collected_by_default() {
+        let prefs = Prefs::default();
+        assert!(crate::dice(&[RGB4X4.sprite()], &prefs)
+            .unwrap()
+            .stats
+            .is_none());
+    }
+
+    #[test]
+    fn counts_sprite_units() {
+        // Transparent unit of BGRT is ignored.
+        let stats = collect(vec![&RGBY, &BGRT], 1, 0);
+        assert_eq!(sprite(&stats, 0).units, 4);
+        assert_eq!(sprite(&stats, 0).unique, 4);
+        assert_eq!(sprite(&stats, 1).units, 3);
+        assert_eq!(sprite(&stats, 1).unique, 3);
+    }
+
+    #[test]
+    fn counts_units_shared_with_other_sprites() {
+        // RGBY shares R, G and B with BGRT and Y with Y1X1.
+        let stats = collect(vec![&RGBY, &BGRT, &Y1X1], 1, 0);
+        assert_eq!(sprite(&stats, 0).shared, 4);
+        assert_eq!(sprite(&stats, 1).shared, 3);
+        assert_eq!(sprite(&stats, 2).shared, 1);
+        let stats = collect(vec![&RGBY, &BGRT], 1, 0);
+        assert_eq!(sprite(&stats, 0).shared, 3);
+        assert_eq!(sprite(&stats, 1).shared, 3);
+    }
+
+    #[test]
+    fn repeated_units_of_single_sprite_are_not_shared() {
+        let stats = collect(vec![&RGB4X4, &Y1X1], 1, 0);
+        assert_eq!(sprite(&stats, 0).units, 16);
+        assert_eq!(sprite(&stats, 0).unique, 3);
+        assert_eq!(sprite(&stats, 0).shared, 0);
+        assert_eq!(sprite(&stats, 1).shared, 0);
+    }
+
+    #[test]
+    fn sprite_mesh_size_is_evaluated() {
+        let stats = collect(vec![&RGBY], 1, 0);
+        assert_eq!(sprite(&stats, 0).vertices, 16);
+        assert_eq!(sprite(&stats, 0).indices, 24);
+        assert_eq!(
+            sprite(&stats, 0).mesh_bytes,
+            16 * (size_of::<Vertex>() + size_of::<Uv>()) + 24 * size_of::<usize>()
+        );
+        assert_eq!(stats.mesh_bytes, sprite(&stats, 0).mesh_bytes);
+    }
+
+    #[test]
+    fn atlas_occupancy_accounts_padding() {
+        // 4 units with 1px padding occupy 3x3 cells filling the whole 6x6 atlas.
+        let stats = collect(vec![&RGBY], 1, 1);
+        assert_eq!(stats.atlases[0].size, USize::new(6, 6));
+        assert_eq!(stats.atlases[0].units, 4);
+        assert_eq!(stats.atlases[0].wasted_texels, 0);
+        assert_eq!(stats.atlases[0].occupancy, 1.0);
+    }
+
+    #[test]
+    fn atlas_wasted_texels_are_evaluated() {
+        // 3 units packed into 2x2 atlas waste a single texel.
+        let stats = collect(vec![&BGRT], 1, 0);
+        assert_eq!(stats.atlases[0].units, 3);
+        assert_eq!(stats.atlases[0].wasted_texels, 1);
+        assert_eq!(stats.atlases[0].occupancy, 0.75);
+    }
+
+    #[test]
+    fn byte_totals_are_evaluated() {
+        let stats = collect(vec![&RGBY, &RGBY], 1, 0);
+        assert_eq!(stats.source_bytes, 2 * 4 * 4);
+        assert_eq!(stats.atlas_bytes, 4 * 4);
+        assert_eq!(
+            stats.compression_ratio(),
+            32.0 / (16 + stats.mesh_bytes) as f32
+        );
+    }
+
+    #[test]
+    fn atlas_bytes_include_mips() {
+        let prefs = Prefs {
+            unit_size: 2,
+            padding: 0,
+            mip_levels: 1,
+            collect_stats: true,
+            ..Prefs::default()
+        };
+        let stats = crate::dice(&[RGB4X4.sprite()], &prefs)
+            .unwrap()
+            .stats
+            .unwrap();
+        assert_eq!(stats.atlas_bytes, (16 + 4) * 4);
+    }
+
+    fn collect(src: Vec<&dyn AnySource>, unit_size: u32, padding: u32) -> Stats {
+        let mut sprites = src.iter().map(|s| s.sprite()).collect::<Vec<_>>();
+        for (idx, sprite) in sprites.iter_mut().enumerate() {
+            sprite.id = idx.to_string();
+        }
+        let prefs = Prefs {
+            unit_size,
+            padding,
+            atlas_square: true,
+            collect_stats: true,
+            ..Prefs::default()
+        };
+        crate::dice(&sprites, &prefs).unwrap().stats.unwrap()
+    }
+
+    fn sprite(stats: &Stats, idx: usize) -> &SpriteStats {
+        let id = idx.to_string();
+        stats.sprites.iter().find(|s| s.id == id).unwrap()
+    }
+}
@@ -0,0 +1,326 @@
+use crate::models::*;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Tint which doesn't change the tinted color.
+pub(crate) const WHITE: Pixel = Pixel::new(255, 255, 255, 255);
+
+/// Finds units which content is an exact tinted (multiplied by a color) copy of other units
+/// and maps them to the originals (base units): the copies are assigned hash and pixels of
+/// the base, so that they share its atlas cell, and the tint to apply when rendering.
+pub(crate) fn dedupe(diced: &mut [DicedTexture], prefs: &Prefs) {
+    let cell = Cell::new(prefs);
+    let mut bases: Vec<Base> = vec![];
+    let mut buckets = HashMap::<Key, Vec<usize>>::new();
+    let mut tinted = HashMap::<u64, (usize, Pixel)>::new();
+
+    // Tinting never brightens the content, so the brightest units are tried as bases first.
+    for (hash, pixels) in collect_distinct(diced, &cell, prefs.unit_size) {
+        let clear = hash_clear(&pixels);
+        let found = find_candidates(&buckets, clear, &pixels)
+            .into_iter()
+            .find_map(|idx| {
+                let tint = eval_tint(&bases[idx].content, &pixels)?;
+                Some((idx, tint))
+            });
+        match found {
+            Some(base) => _ = tinted.insert(hash, base),
+            None => {
+                for ch in 0..4 {
+                    let key = (clear, ch, max_positions(&pixels, ch)[0]);
+                    buckets.entry(key).or_default().push(bases.len());
+                }
+                bases.push(Base {
+                    hash,
+                    content: pixels,
+                    padded: vec![],
+                });
+            }
+        }
+    }
+
+    if tinted.is_empty() {
+        return;
+    }
+    let indices = bases.iter().enumerate().map(|(idx, b)| (b.hash, idx));
+    let indices = indices.collect::<HashMap<_, _>>();
+    for unit in diced.iter().flat_map(|d| d.units.iter()) {
+        if let Some(base) = indices.get(&unit.hash).map(|idx| &mut bases[*idx]) {
+            if base.padded.is_empty() {
+                base.padded = unit.pixels.to_owned();
+            }
+        }
+    }
+    for tex in diced.iter_mut() {
+        for unit in tex.units.iter_mut() {
+            if let Some((idx, tint)) = tinted.get(&unit.hash) {
+                unit.hash = bases[*idx].hash;
+                unit.pixels = bases[*idx].padded.to_owned();
+                unit.tint = *tint;
+            }
+        }
+        tex.unique = tex.units.iter().map(|u| u.hash).collect();
+    }
+}
+
+/// Multiplies the color by the tint, as done by GPU with 8-bit normalized channels.
+pub(crate) fn multiply(color: Pixel, tint: Pixel) -> Pixel {
+    let [r, g, b, a] = color.to_raw();
+    let [tr, tg, tb, ta] = tint.to_raw();
+    Pixel::new(mul(r, tr), mul(g, tg), mul(b, tb), mul(a, ta))
+}
+
+/// Bucket of the base units: hash of the clear pixel positions, channel index and position
+/// of the first pixel with the max value of the channel.
+type Key = (u64, usize, usize);
+
+struct Base {
+    hash: u64,
+    /// Non-padded pixels of the unit.
+    content: Vec<Pixel>,
+    /// Padded pixels of the unit, as baked into the atlas.
+    padded: Vec<Pixel>,
+}
+
+/// Collects non-padded pixels of the units with distinct content, brightest first.
+fn collect_distinct(diced: &[DicedTexture], cell: &Cell, size: u32) -> Vec<(u64, Vec<Pixel>)> {
+    let mut distinct = HashMap::new();
+    for unit in diced.iter().flat_map(|d| d.units.iter()) {
+        distinct
+            .entry(unit.hash)
+            .or_insert_with(|| get_content(&unit.pixels, cell, size));
+    }
+    let mut distinct = distinct.into_iter().collect::<Vec<_>>();
+    distinct.sort_by_cached_key(|(hash, pixels)| (Reverse(brightness(pixels)), *hash));
+    distinct
+}
+
+fn get_content(padded: &[Pixel], cell: &Cell, size: u32) -> Vec<Pixel> {
+    let stride = cell.padded_size(size) as usize;
+    let (pad, size) = (cell.pad as usize, size as usize);
+    let rows = padded.chunks_exact(stride).skip(pad).take(size);
+    rows.flat_map(|row| &row[pad..pad + size])
+        .copied()
+        .collect()
+}
+
+fn brightness(pixels: &[Pixel]) -> u64 {
+    let channels = pixels.iter().flat_map(|p| p.to_raw());
+    channels.map(|c| c as u64).sum()
+}
+
+/// Hashes positions of the clear pixels, which are preserved by the tints (unless the alpha
+/// is tinted down to zero), so that only the units with the same shape are compared.
+fn hash_clear(pixels: &[Pixel]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for pixel in pixels {
+        (pixel.a() == 0).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Finds indices of the bases the pixels may be a tinted copy of, in ascending order.
+/// Tinting preserves order of the channel values (though some may become equal due to
+/// rounding), so the first max pixel of a base channel is among the max pixels of its copies;
+/// the channel with the fewest max pixels narrows the search down the most.
+fn find_candidates(buckets: &HashMap<Key, Vec<usize>>, clear: u64, pixels: &[Pixel]) -> Vec<usize> {
+    let positions = (0..4).map(|ch| (ch, max_positions(pixels, ch)));
+    let positions = positions.filter(|(_, p)| !p.is_empty());
+    let Some((ch, positions)) = positions.min_by_key(|(_, p)| p.len()) else {
+        return vec![];
+    };
+    let buckets = positions
+        .iter()
+        .filter_map(|pos| buckets.get(&(clear, ch, *pos)));
+    let mut candidates = buckets.flatten().copied().collect::<Vec<_>>();
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+}
+
+/// Finds positions of the pixels with the max value of the channel; color of the clear
+/// pixels is ignored, so only the alpha channel (index 3) is never empty.
+fn max_positions(pixels: &[Pixel], ch: usize) -> Vec<usize> {
+    let values = pixels.iter().map(|p| p.to_raw());
+    let values = values.enumerate().filter(|(_, p)| ch == 3 || p[3] > 0);
+    let max = values.clone().map(|(_, p)| p[ch]).max();
+    let max = values.filter(|(_, p)| Some(p[ch]) == max);
+    max.map(|(idx, _)| idx).collect()
+}
+
+/// Evaluates tint reproducing the target pixels from the base pixels exactly, if any;
+/// color of the fully transparent target pixels is ignored.
+fn eval_tint(base: &[Pixel], target: &[Pixel]) -> Option<Pixel> {
+    let mut ranges = [(0, 255); 4];
+    for (base, target) in base.iter().zip(target) {
+        let (base, target) = (base.to_raw(), target.to_raw());
+        let first = if target[3] == 0 { 3 } else { 0 };
+        for ch in first..4 {
+            let (min, max) = eval_range(base[ch], target[ch])?;
+            let range = &mut ranges[ch];
+            *range = (range.0.max(min), range.1.min(max));
+            if range.0 > range.1 {
+                return None;
+            }
+        }
+    }
+    let [r, g, b, a] = ranges.map(|(_, max)| max as u8);
+    Some(Pixel::new(r, g, b, a))
+}
+
+/// Evaluates range of the tint channel values for which the base channel is multiplied
+/// to the target value.
+fn eval_range(base: u8, target: u8) -> Option<(u32, u32)> {
+    let (base, target) = (base as u32, target as u32);
+    if base == 0 {
+        return (target == 0).then_some((0, 255));
+    }
+    // Solving (2 * base * tint + 255) / 510 == target for tint.
+    let min = match target {
+        0 => 0,
+        _ => (510 * target - 255).div_ceil(2 * base),
+    };
+    let max = ((510 * target + 255).div_ceil(2 * base) - 1).min(255);
+    (min <= max).then_some((min, max))
+}
+
+fn mul(color: u8, tint: u8) -> u8 {
+    ((2 * color as u32 * tint as u32 + 255) / 510) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn multiply_by_white_preserves_color() {
+        for c in 0..=255 {
+            assert_eq!(
+                multiply(Pixel::new(c, c, c, c), WHITE),
+                Pixel::new(c, c, c, c)
+            );
+        }
+    }
+
+    #[test]
+    fn multiply_rounds_to_nearest() {
+        let color = multiply(Pixel::new(255, 100, 1, 255), Pixel::new(128, 128, 127, 0));
+        assert_eq!(color, Pixel::new(128, 50, 0, 0));
+    }
+
+    #[test]
+    fn evaluated_tint_reproduces_target() {
+        let base = [Pixel::new(255, 200, 0, 255), Pixel::new(100, 50, 0, 128)];
+        let tint = Pixel::new(200, 100, 30, 255);
+        let target = base.map(|p| multiply(p, tint));
+        let eval = eval_tint(&base, &target).unwrap();
+        assert_eq!(base.map(|p| multiply(p, eval)), target);
+    }
+
+    #[test]
+    fn tint_is_not_found_for_different_content() {
+        let base = [Pixel::new(255, 0, 0, 255), Pixel::new(100, 0, 0, 255)];
+        let target = [Pixel::new(100, 0, 0, 255), Pixel::new(100, 0, 0, 255)];
+        assert_eq!(eval_tint(&base, &target), None);
+        assert_eq!(eval_tint(&[Pixel::new(0, 0, 0, 255)], &[R]), None);
+    }
+
+    #[test]
+    fn base_max_is_among_max_of_tinted_copy() {
+        // Red of 254 and 255 become equal when halved, while blue is zeroed altogether.
+        let base = [
+            Pixel::new(254, 0, 9, 255),
+            Pixel::new(255, 0, 0, 255),
+            Pixel::new(0, 0, 0, 0),
+        ];
+        let copy = base.map(|p| multiply(p, Pixel::new(127, 255, 0, 255)));
+        for ch in 0..4 {
+            assert!(max_positions(&copy, ch).contains(&max_positions(&base, ch)[0]));
+        }
+        assert_eq!(max_positions(&base, 0), vec![1]);
+        assert_eq!(max_positions(&copy, 0), vec![0, 1]);
+        assert_eq!(max_positions(&base, 3), vec![0, 1]);
+    }
+
+    #[test]
+    fn units_are_only_compared_with_bases_sharing_max_positions() {
+        let mut buckets = HashMap::new();
+        let base = [R, B, T];
+        for ch in 0..4 {
+            buckets.insert((0, ch, max_positions(&base, ch)[0]), vec![0]);
+        }
+        let copy = base.map(|p| multiply(p, Pixel::new(100, 100, 100, 255)));
+        assert_eq!(find_candidates(&buckets, 0, &copy), vec![0]);
+        assert!(find_candidates(&buckets, 0, &[B, R, T]).is_empty());
+    }
+
+    #[test]
+    fn color_of_clear_target_pixels_is_ignored() {
+        let base = [Pixel::new(255, 255, 255, 255), Pixel::new(255, 255, 255, 0)];
+        let target = [Pixel::new(255, 0, 0, 255), Pixel::new(0, 255, 0, 0)];
+        assert_eq!(eval_tint(&base, &target), Some(Pixel::new(255, 0, 0, 255)));
+    }
+
+    #[test]
+    fn tinted_copies_share_base_units() {
+        let prefs = Prefs {
+            unit_size: 2,
+            padding: 1,
+            dedupe_tints: true,
+            ..Prefs::default()
+        };
+        let sprites = sources();
+        let diced = crate::dicer::dice(&sprites, &prefs).unwrap();
+        assert_eq!(diced[0].unique, diced[1].unique);
+        for (copy, base) in diced[0].units.iter().zip(diced[1].units.iter()) {
+            assert_eq!(copy.hash, base.hash);
+            assert_eq!(copy.pixels, base.pixels);
+            assert_eq!(base.tint, WHITE);
+            assert_ne!(copy.tint, WHITE);
+        }
+    }
+
+    #[test]
+    fn tinted_copies_are_reconstructed() {
+        let prefs = Prefs {
+            unit_size: 2,
+            padding: 1,
+            dedupe_tints: true,
+            ..Prefs::default()
+        };
+        let sprites = sources();
+        let arts = crate::dice(&sprites, &prefs).unwrap();
+        let copy = &arts.sprites.iter().find(|s| s.id == "copy").unwrap();
+        assert_eq!(copy.colors.len(), copy.vertices.len());
+        assert!(crate::verify(&sprites, &arts, &prefs).is_empty());
+    }
+
+    #[test]
+    fn tinted_copies_are_not_merged_when_disabled() {
+        let prefs = Prefs {
+            unit_size: 2,
+            ..Prefs::default()
+        };
+        let diced = crate::dicer::dice(&sources(), &prefs).unwrap();
+        assert!(diced[0].unique.is_disjoint(&diced[1].unique));
+        assert!(diced[0].units.iter().all(|u| u.tint == WHITE));
+    }
+
+    /// Copy of the base sprite tinted with a color, followed by the base sprite.
+    fn sources() -> Vec<SourceSprite> {
+        let tint = Pixel::new(255, 128, 64, 255);
+        let pixels = RGB4X4.pixels.iter().map(|p| multiply(*p, tint)).collect();
+        let copy = Texture {
+            width: RGB4X4.width,
+            height: RGB4X4.height,
+            pixels,
+        };
+        let copy = SourceSprite {
+            id: "copy".to_owned(),
+            ..copy.sprite()
+        };
+        vec![copy, RGB4X4.sprite()]
+    }
+}
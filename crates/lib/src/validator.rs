@@ -0,0 +1,332 @@
+use crate::models::*;
+use std::collections::{HashMap, HashSet};
+
+/// Checks the source sprites and preferences up front, so that malformed input is reported
+/// with a descriptive error instead of failing (or producing ambiguous output) mid-operation.
+pub(crate) fn validate(sprites: &[SourceSprite], prefs: &Prefs) -> Result<()> {
+    if !prefs.ppu.is_finite() {
+        return Err(Error::InvalidPpu { ppu: prefs.ppu });
+    }
+    if !(0.0..=0.5).contains(&prefs.uv_inset) {
+        return Err(Error::InvalidUvInset {
+            uv_inset: prefs.uv_inset,
+        });
+    }
+    if let Some(tolerance) = prefs.outline_tolerance {
+        if !tolerance.is_finite() || tolerance < 0.0 {
+            return Err(Error::InvalidOutlineTolerance { tolerance });
+        }
+    }
+    check_pivot(None, &prefs.pivot)?;
+
+    let mut ids = HashSet::with_capacity(sprites.len());
+    let mut sequences = HashMap::new();
+    for (index, sprite) in sprites.iter().enumerate() {
+        if sprite.id.is_empty() {
+            return Err(Error::EmptyId { index });
+        }
+        if !ids.insert(&sprite.id) {
+            let sprite_id = sprite.id.to_owned();
+            return Err(Error::DuplicateId { sprite_id });
+        }
+        check_texture(sprite)?;
+        if let Some(pivot) = &sprite.pivot {
+            check_pivot(Some(&sprite.id), pivot)?;
+        }
+        if let Some(border) = &sprite.border {
+            check_border(sprite, border)?;
+        }
+        check_overrides(sprite, prefs)?;
+        if let Some(sequence) = &sprite.sequence {
+            check_frame(sprite, sequences.entry(sequence).or_insert(sprite))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn check_texture(sprite: &SourceSprite) -> Result<()> {
+    let tex = &sprite.texture;
+    if tex.width == 0
+        || tex.height == 0
+        || tex.pixels.len() != tex.width as usize * tex.height as usize
+    {
+        return Err(Error::MalformedTexture {
+            sprite_id: sprite.id.to_owned(),
+            width: tex.width,
+            height: tex.height,
+            pixels: tex.pixels.len(),
+        });
+    }
+    Ok(())
+}
+
+fn check_border(sprite: &SourceSprite, border: &Border) -> Result<()> {
+    let tex = &sprite.texture;
+    if border.left as u64 + border.right as u64 <= tex.width as u64
+        && border.top as u64 + border.bottom as u64 <= tex.height as u64
+    {
+        return Ok(());
+    }
+    Err(Error::InvalidBorder {
+        sprite_id: sprite.id.to_owned(),
+        border: border.to_owned(),
+    })
+}
+
+fn check_overrides(sprite: &SourceSprite, prefs: &Prefs) -> Result<()> {
+    if let Some(ppu) = sprite.ppu.filter(|ppu| !ppu.is_finite() || *ppu <= 0.0) {
+        return Err(Error::InvalidSpritePpu {
+            sprite_id: sprite.id.to_owned(),
+            ppu,
+        });
+    }
+    if let Some(padding) = sprite.padding.filter(|pad| *pad > prefs.padding) {
+        return Err(Error::SpritePaddingTooLarge {
+            sprite_id: sprite.id.to_owned(),
+            padding,
+            max_padding: prefs.padding,
+        });
+    }
+    Ok(())
+}
+
+fn check_frame(sprite: &SourceSprite, first: &SourceSprite) -> Result<()> {
+    let (tex, first_tex) = (&sprite.texture, &first.texture);
+    if tex.width == first_tex.width
+        && tex.height == first_tex.height
+        && sprite.ppu == first.ppu
+        && sprite.trim_transparent == first.trim_transparent
+    {
+        return Ok(());
+    }
+    Err(Error::MismatchedSequence {
+        sprite_id: sprite.id.to_owned(),
+        sequence: first.sequence.to_owned().unwrap_or_default(),
+    })
+}
+
+fn check_pivot(sprite_id: Option<&str>, pivot: &Pivot) -> Result<()> {
+    if pivot.x.is_finite() && pivot.y.is_finite() {
+        return Ok(());
+    }
+    Err(Error::InvalidPivot {
+        sprite_id: sprite_id.map(str::to_owned),
+        x: pivot.x,
+        y: pivot.y,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::*;
+    use crate::models::*;
+
+    #[test]
+    fn valid_sources_pass() {
+        let sprites = sprites(vec![&RGB4X4, &(&B1X1, (0.0, 1.0))]);
+        assert!(validate(&sprites, &Prefs::default()).is_ok());
+    }
+
+    #[test]
+    fn errs_when_pixel_count_mismatches_dimensions() {
+        let mut sprites = sprites(vec![&RGB4X4]);
+        sprites[0].texture.pixels.pop();
+        assert!(validate(&sprites, &Prefs::default()).is_err_and(|e| e
+            == Error::MalformedTexture {
+                sprite_id: "0".to_owned(),
+                width: 4,
+                height: 4,
+                pixels: 15
+            }
+            && e.to_string() == "Texture of sprite '0' has 15 pixels, while 4x4 is expected."));
+    }
+
+    #[test]
+    fn errs_when_texture_has_zero_size() {
+        let mut sprites = sprites(vec![&R1X1, &B1X1]);
+        sprites[1].texture = Texture {
+            width: 0,
+            height: 1,
+            pixels: vec![],
+        };
+        assert!(validate(&sprites, &Prefs::default())
+            .is_err_and(|e| e.to_string() == "Texture of sprite '1' has zero size (0x1)."));
+    }
+
+    #[test]
+    fn errs_when_id_is_empty() {
+        let mut sprites = sprites(vec![&R1X1, &B1X1]);
+        sprites[1].id = "".to_owned();
+        assert!(
+            validate(&sprites, &Prefs::default()).is_err_and(|e| e == Error::EmptyId { index: 1 })
+        );
+    }
+
+    #[test]
+    fn errs_when_ids_are_duplicated() {
+        let mut sprites = sprites(vec![&R1X1, &B1X1, &G1X1]);
+        sprites[2].id = "0".to_owned();
+        assert!(validate(&sprites, &Prefs::default()).is_err_and(|e| e
+            == Error::DuplicateId {
+                sprite_id: "0".to_owned()
+            }));
+    }
+
+    #[test]
+    fn errs_when_sprite_pivot_is_nan() {
+        let sprites = sprites(vec![&(&R1X1, (f32::NAN, 0.0))]);
+        assert!(validate(&sprites, &Prefs::default()).is_err_and(
+            |e| e.to_string() == "Pivot (NaN, 0) of sprite '0' is not a finite number."
+        ));
+    }
+
+    #[test]
+    fn errs_when_default_pivot_is_nan() {
+        let prefs = Prefs {
+            pivot: Pivot::new(0.0, f32::NAN),
+            ..Prefs::default()
+        };
+        assert!(
+            validate(&sprites(vec![&R1X1]), &prefs).is_err_and(|e| matches!(
+                e,
+                Error::InvalidPivot {
+                    sprite_id: None,
+                    ..
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn errs_when_border_exceeds_texture() {
+        let mut sprites = sprites(vec![&RGB4X4]);
+        sprites[0].border = Some(Border::new(0, 0, 4, 0));
+        assert!(validate(&sprites, &Prefs::default()).is_ok());
+        sprites[0].border = Some(Border::new(2, 3, 0, 0));
+        assert!(
+            validate(&sprites, &Prefs::default()).is_err_and(|e| e.to_string()
+                == "Border (2, 3, 0, 0) of sprite '0' exceeds its texture dimensions."
+                && e.code() == 10)
+        );
+    }
+
+    #[test]
+    fn errs_when_sprite_ppu_is_not_positive() {
+        for ppu in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let mut sprite = R1X1.sprite();
+            sprite.ppu = Some(ppu);
+            assert!(validate(&[sprite], &Prefs::default()).is_err_and(|e| {
+                e.code() == 11
+                    && e.to_string()
+                        == format!(
+                            "PPU ({ppu}) of sprite 'TEST' should be a positive finite number."
+                        )
+            }));
+        }
+    }
+
+    #[test]
+    fn errs_when_sprite_padding_exceeds_prefs() {
+        let mut sprite = R1X1.sprite();
+        sprite.padding = Some(3);
+        let prefs = Prefs {
+            padding: 2,
+            ..Prefs::default()
+        };
+        assert!(validate(&[sprite.clone()], &prefs).is_err_and(|e| e
+            == Error::SpritePaddingTooLarge {
+                sprite_id: "TEST".to_owned(),
+                padding: 3,
+                max_padding: 2
+            }));
+        sprite.padding = Some(2);
+        assert!(validate(&[sprite], &prefs).is_ok());
+    }
+
+    #[test]
+    fn errs_when_sequence_frames_mismatch() {
+        let mut sprites = sprites(vec![&RGB4X4, &RGB4X4, &R1X1]);
+        for sprite in sprites.iter_mut() {
+            sprite.sequence = Some("walk".to_owned());
+        }
+        assert!(validate(&sprites[..2], &Prefs::default()).is_ok());
+        assert!(
+            validate(&sprites, &Prefs::default()).is_err_and(|e| e.code() == 12
+                && e.to_string()
+                    == "Sprite '2' differs from the first frame of sequence 'walk' \
+                in texture size, PPU or trim.")
+        );
+        sprites[1].ppu = Some(1.0);
+        assert!(validate(&sprites[..2], &Prefs::default()).is_err_and(|e| e.code() == 12));
+        sprites[1].ppu = None;
+        sprites[1].trim_transparent = Some(true);
+        assert!(validate(&sprites[..2], &Prefs::default()).is_err_and(|e| e.code() == 12));
+    }
+
+    #[test]
+    fn errs_when_ppu_is_not_finite() {
+        for ppu in [f32::NAN, f32::INFINITY] {
+            let prefs = Prefs {
+                ppu,
+                ..Prefs::default()
+            };
+            assert!(validate(&sprites(vec![&R1X1]), &prefs)
+                .is_err_and(|e| matches!(e, Error::InvalidPpu { .. })));
+        }
+    }
+
+    #[test]
+    fn errs_when_uv_inset_is_not_finite() {
+        for uv_inset in [f32::NAN, f32::NEG_INFINITY] {
+            let prefs = Prefs {
+                uv_inset,
+                ..Prefs::default()
+            };
+            assert!(validate(&sprites(vec![&R1X1]), &prefs)
+                .is_err_and(|e| matches!(e, Error::InvalidUvInset { .. })));
+        }
+    }
+
+    #[test]
+    fn errs_when_uv_inset_is_negative() {
+        let prefs = Prefs {
+            uv_inset: -0.1,
+            ..Prefs::default()
+        };
+        assert!(validate(&sprites(vec![&R1X1]), &prefs)
+            .is_err_and(|e| e == Error::InvalidUvInset { uv_inset: -0.1 }));
+    }
+
+    #[test]
+    fn errs_when_outline_tolerance_is_negative_or_not_finite() {
+        for tolerance in [-1.0, f32::NAN, f32::INFINITY] {
+            let prefs = Prefs {
+                outline_tolerance: Some(tolerance),
+                ..Prefs::default()
+            };
+            assert!(validate(&sprites(vec![&R1X1]), &prefs)
+                .is_err_and(|e| matches!(e, Error::InvalidOutlineTolerance { .. })));
+        }
+    }
+
+    #[test]
+    fn dice_and_estimate_validate_sources() {
+        let sprites = vec![R1X1.sprite(), B1X1.sprite()];
+        let prefs = Prefs::default();
+        assert!(crate::dice(&sprites, &prefs).is_err_and(|e| e.code() == 9));
+        assert!(crate::estimate(&sprites, &prefs).is_err_and(|e| e.code() == 9));
+    }
+
+    fn validate(sprites: &[SourceSprite], prefs: &Prefs) -> Result<()> {
+        crate::validator::validate(sprites, prefs)
+    }
+
+    fn sprites(src: Vec<&dyn AnySource>) -> Vec<SourceSprite> {
+        let mut sprites = src.iter().map(|s| s.sprite()).collect::<Vec<_>>();
+        for (idx, sprite) in sprites.iter_mut().enumerate() {
+            sprite.id = idx.to_string();
+        }
+        sprites
+    }
+}
@@ -0,0 +1,351 @@
+use crate::models::*;
+use crate::tinter;
+
+/// Rasterizes the diced sprite mesh sampling the atlas textures back into the source texture space.
+pub(crate) fn reconstruct(sprite: &DicedSprite, atlases: &[Texture], prefs: &Prefs) -> Texture {
+    let sprite = &crate::builder::convert(sprite.to_owned(), &prefs.coordinates);
+    let proj = Projection::new(sprite);
+    let mut texture = Texture {
+        width: proj.width,
+        height: proj.height,
+        pixels: vec![Pixel::default(); (proj.width * proj.height) as usize],
+    };
+    for face in sprite.indices.chunks_exact(3) {
+        let points = [0, 1, 2].map(|i| to_point(&proj, sprite, face[i]));
+        // Faces never span multiple atlases, as all the vertices of a unit sample the same one.
+        let atlas_idx = sprite.atlas_indices.get(face[0]);
+        let atlas = &atlases[*atlas_idx.unwrap_or(&sprite.atlas_index)];
+        draw_triangle(&points, atlas, &mut texture);
+    }
+    texture
+}
+
+/// Reconstructs the diced sprites and compares them with the associated sources.
+pub(crate) fn verify(sources: &[SourceSprite], arts: &Artifacts, prefs: &Prefs) -> Vec<Mismatch> {
+    let mut mismatches = vec![];
+    for (idx, source) in sources.iter().enumerate() {
+        report(prefs, idx, sources.len());
+        let sprite = arts.sprites.iter().find(|s| s.id == source.id);
+        let texture = match sprite {
+            Some(s) => reconstruct(arts.resolve(s), &arts.atlases, prefs),
+            None => Texture {
+                width: 0,
+                height: 0,
+                pixels: vec![],
+            },
+        };
+        if let Some(mismatch) = compare(&source.id, &source.texture, &texture) {
+            mismatches.push(mismatch);
+        }
+    }
+    mismatches
+}
+
+fn report(prefs: &Prefs, idx: usize, len: usize) {
+    if let Some(cb) = &prefs.on_progress {
+        let ratio = idx as f32 / len as f32;
+        let activity = format!("Verifying diced sprites... ({} of {len})", idx + 1);
+        cb(Progress { ratio, activity });
+    }
+}
+
+/// Mapping of the diced sprite mesh to the source texture space.
+pub(crate) struct Projection {
+    ppu: f32,
+    /// Position of the sprite mesh origin in the source texture, in pixels.
+    origin: (f32, f32),
+    /// Width of the reconstructed texture, in pixels.
+    pub width: u32,
+    /// Height of the reconstructed texture, in pixels.
+    pub height: u32,
+}
+
+impl Projection {
+    /// Resolves the mapping of the specified sprite (in the default coordinates); the PPU is
+    /// evaluated from the sprite rect, as it may be overridden per sprite.
+    pub fn new(sprite: &DicedSprite) -> Self {
+        let px = &sprite.pixel_rect;
+        // Trimmed pixel rect is positioned relative to top-left corner of the source texture,
+        // while the source size restores the trimmed transparent area on all the sides.
+        Projection {
+            ppu: px.width as f32 / sprite.rect.width,
+            origin: (
+                px.x as f32 + sprite.pixel_pivot.x,
+                px.y as f32 + sprite.pixel_pivot.y,
+            ),
+            width: sprite.source_size.width,
+            height: sprite.source_size.height,
+        }
+    }
+
+    /// Projects specified mesh vertex to the source texture space, in pixels.
+    pub fn project(&self, vertex: &Vertex) -> (f32, f32) {
+        (
+            vertex.x * self.ppu + self.origin.0,
+            vertex.y * self.ppu + self.origin.1,
+        )
+    }
+}
+
+/// Mesh vertex projected to the pixel space of the reconstructed texture with associated
+/// UV and color.
+struct Point {
+    x: f32,
+    y: f32,
+    uv: Uv,
+    color: Pixel,
+}
+
+fn to_point(proj: &Projection, sprite: &DicedSprite, idx: usize) -> Point {
+    let (x, y) = proj.project(&sprite.vertices[idx]);
+    let uv = sprite.uvs[idx].to_owned();
+    let color = sprite.colors.get(idx).copied().unwrap_or(tinter::WHITE);
+    Point { x, y, uv, color }
+}
+
+fn draw_triangle(points: &[Point; 3], atlas: &Texture, texture: &mut Texture) {
+    let [a, b, c] = points;
+    let area = edge(a, b, c.x, c.y);
+    if area == 0.0 {
+        return;
+    }
+    let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as u32;
+    let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as u32;
+    let max_x = (a.x.max(b.x).max(c.x).ceil() as u32).min(texture.width);
+    let max_y = (a.y.max(b.y).max(c.y).ceil() as u32).min(texture.height);
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            // Sampling at pixel centers, which are never exactly on the unit edges.
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let wa = edge(b, c, px, py) / area;
+            let wb = edge(c, a, px, py) / area;
+            let wc = edge(a, b, px, py) / area;
+            if wa < -f32::EPSILON || wb < -f32::EPSILON || wc < -f32::EPSILON {
+                continue;
+            }
+            let u = wa * a.uv.u + wb * b.uv.u + wc * c.uv.u;
+            let v = wa * a.uv.v + wb * b.uv.v + wc * c.uv.v;
+            let idx = (x + y * texture.width) as usize;
+            // Colors are the same for all the vertices of a unit, so aren't interpolated.
+            texture.pixels[idx] = tinter::multiply(sample(atlas, u, v), a.color);
+        }
+    }
+}
+
+fn edge(a: &Point, b: &Point, x: f32, y: f32) -> f32 {
+    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+fn sample(atlas: &Texture, u: f32, v: f32) -> Pixel {
+    let x = ((u * atlas.width as f32) as u32).min(atlas.width - 1);
+    let y = ((v * atlas.height as f32) as u32).min(atlas.height - 1);
+    atlas.pixels[(x + y * atlas.width) as usize]
+}
+
+fn compare(id: &str, source: &Texture, texture: &Texture) -> Option<Mismatch> {
+    let mut mismatch: Option<Mismatch> = None;
+    for y in 0..source.height.max(texture.height) {
+        for x in 0..source.width.max(texture.width) {
+            if get_pixel(source, x, y) == get_pixel(texture, x, y) {
+                continue;
+            }
+            match &mut mismatch {
+                Some(m) => m.pixels += 1,
+                None => {
+                    mismatch = Some(Mismatch {
+                        id: id.to_owned(),
+                        pixels: 1,
+                        x,
+                        y,
+                    })
+                }
+            }
+        }
+    }
+    mismatch
+}
+
+/// Returns pixel at the specified position; pixels out of bounds and fully transparent
+/// pixels (regardless of color) are considered clear.
+fn get_pixel(texture: &Texture, x: u32, y: u32) -> Pixel {
+    if x >= texture.width || y >= texture.height {
+        return Pixel::default();
+    }
+    let pixel = texture.pixels[(x + y * texture.width) as usize];
+    if pixel.a() == 0 {
+        Pixel::default()
+    } else {
+        pixel
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::*;
+    use crate::models::*;
+
+    #[test]
+    fn reconstructs_diced_sprite() {
+        let prefs = defaults();
+        let sprite = RGB4X4.sprite();
+        let arts = crate::dice(&[sprite.to_owned()], &prefs).unwrap();
+        let texture = reconstruct(&arts, &prefs);
+        assert_eq!(texture.width, 4);
+        assert_eq!(texture.height, 4);
+        assert_eq!(texture.pixels, sprite.texture.pixels);
+    }
+
+    #[test]
+    fn reconstructs_with_ppu_and_pivot() {
+        let prefs = Prefs {
+            ppu: 100.0,
+            pivot: Pivot::new(0.3, 0.9),
+            ..defaults()
+        };
+        let arts = crate::dice(&[RGB4X4.sprite()], &prefs).unwrap();
+        assert_eq!(reconstruct(&arts, &prefs).pixels, RGB4X4.pixels);
+    }
+
+    #[test]
+    fn reconstructs_with_per_sprite_pivot() {
+        let prefs = defaults();
+        let arts = crate::dice(&[(&RGB4X4, (1.0, 0.0)).sprite()], &prefs).unwrap();
+        assert_eq!(reconstruct(&arts, &prefs).pixels, RGB4X4.pixels);
+    }
+
+    #[test]
+    fn reconstructs_with_padding_and_units_cropped_at_edges() {
+        let prefs = Prefs {
+            unit_size: 3,
+            padding: 2,
+            ..defaults()
+        };
+        let arts = crate::dice(&[RGB4X4.sprite()], &prefs).unwrap();
+        assert_eq!(reconstruct(&arts, &prefs).pixels, RGB4X4.pixels);
+    }
+
+    #[test]
+    fn reconstructs_with_any_coordinates() {
+        for (y_axis, uv_origin, winding) in [
+            (YAxis::Up, UvOrigin::TopLeft, Winding::CounterClockwise),
+            (YAxis::Down, UvOrigin::BottomLeft, Winding::Clockwise),
+            (YAxis::Up, UvOrigin::BottomLeft, Winding::Clockwise),
+        ] {
+            let prefs = Prefs {
+                trim_transparent: true,
+                pivot: Pivot::new(0.2, 0.7),
+                coordinates: Coordinates {
+                    y_axis,
+                    uv_origin,
+                    winding,
+                },
+                ..defaults()
+            };
+            let sources = with_ids([RGB4X4.sprite(), TTTM.sprite()]);
+            let arts = crate::dice(&sources, &prefs).unwrap();
+            assert!(crate::verify(&sources, &arts, &prefs).is_empty());
+        }
+    }
+
+    #[test]
+    fn when_trimmed_restores_transparent_area() {
+        let prefs = Prefs {
+            trim_transparent: true,
+            ..defaults()
+        };
+        for source in [&TTTM, &MTTT, &BTGT] {
+            let arts = crate::dice(&[source.sprite()], &prefs).unwrap();
+            let texture = reconstruct(&arts, &prefs);
+            assert_eq!(texture.width, source.width);
+            assert_eq!(texture.height, source.height);
+            assert_eq!(texture.pixels, source.pixels);
+        }
+    }
+
+    #[test]
+    fn verifies_lossless_dicing() {
+        let prefs = Prefs {
+            trim_transparent: true,
+            ..defaults()
+        };
+        let sources = [RGB4X4.sprite(), BTGT.sprite(), MTTT.sprite()];
+        let sources = with_ids(sources);
+        let arts = crate::dice(&sources, &prefs).unwrap();
+        assert!(crate::verify(&sources, &arts, &prefs).is_empty());
+    }
+
+    #[test]
+    fn reports_mismatched_pixels() {
+        let prefs = defaults();
+        let sources = with_ids([RGB4X4.sprite(), RGBY.sprite()]);
+        let mut arts = crate::dice(&sources, &prefs).unwrap();
+        let sprite = arts.sprites.iter().find(|s| s.id == "1").unwrap();
+        let atlas = &mut arts.atlases[sprite.atlas_index];
+        let yellow = atlas.pixels.iter().position(|p| *p == Y).unwrap();
+        atlas.pixels[yellow] = T;
+        let mismatches = crate::verify(&sources, &arts, &prefs);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].id, "1");
+        assert_eq!(mismatches[0].pixels, 1);
+        assert_eq!((mismatches[0].x, mismatches[0].y), (1, 1));
+    }
+
+    #[test]
+    fn reports_missing_sprites() {
+        let prefs = defaults();
+        let sources = with_ids([RGB4X4.sprite(), BTGT.sprite()]);
+        let mut arts = crate::dice(&sources, &prefs).unwrap();
+        arts.sprites.retain(|s| s.id == "0");
+        let mismatches = crate::verify(&sources, &arts, &prefs);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].pixels, 2);
+    }
+
+    #[test]
+    fn clear_sprites_are_not_reported() {
+        let prefs = defaults();
+        let sources = with_ids([RGB4X4.sprite(), TTTT.sprite()]);
+        let arts = crate::dice(&sources, &prefs).unwrap();
+        assert!(crate::verify(&sources, &arts, &prefs).is_empty());
+    }
+
+    #[test]
+    fn transparent_pixels_are_equal_regardless_of_color() {
+        let prefs = defaults();
+        let mut sprite = BTGT.sprite();
+        sprite.texture.pixels[1] = Pixel::new(255, 255, 255, 0);
+        let arts = crate::dice(&[sprite.to_owned()], &prefs).unwrap();
+        assert!(crate::verify(&[sprite], &arts, &prefs).is_empty());
+    }
+
+    #[test]
+    fn reports_progress() {
+        let progress = sample_progress(|p| {
+            let sources = [M1X1.sprite()];
+            let arts = crate::dice(&sources, &defaults()).unwrap();
+            drop(crate::verify(&sources, &arts, &p))
+        });
+        assert_eq!(progress.activity, "Verifying diced sprites... (1 of 1)");
+    }
+
+    fn reconstruct(arts: &Artifacts, prefs: &Prefs) -> Texture {
+        crate::reconstruct(&arts.sprites[0], &arts.atlases, prefs)
+    }
+
+    fn with_ids<const N: usize>(mut sources: [SourceSprite; N]) -> [SourceSprite; N] {
+        for (idx, source) in sources.iter_mut().enumerate() {
+            source.id = idx.to_string();
+        }
+        sources
+    }
+
+    fn defaults() -> Prefs {
+        Prefs {
+            unit_size: 1,
+            padding: 0,
+            trim_transparent: false,
+            ppu: 1.0,
+            ..Prefs::default()
+        }
+    }
+}
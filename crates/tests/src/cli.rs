@@ -4,9 +4,12 @@ use crate::common::*;
 use cli::models::*;
 use rand::{distributions::Alphanumeric, Rng};
 use serde_json::Value;
-use sprite_dicing::{Artifacts, DicedSprite, Pivot, Prefs, Rect, Uv, Vertex};
+use sprite_dicing::{
+    Artifacts, Border, DicedSprite, Pivot, Pixel, Prefs, Rect, Slice, URect, USize, Uv, Vertex,
+};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::{fs, str::FromStr, vec};
+use std::{fs, vec};
 
 #[test]
 fn mono_reproduced() {
@@ -119,6 +122,128 @@ fn can_write_tga() {
     fs::remove_dir_all(out_dir).unwrap();
 }
 
+#[test]
+fn can_write_mips() {
+    let out_dir = create_temp_dir();
+
+    let prefs = Prefs {
+        unit_size: 1,
+        padding: 0,
+        mip_levels: 2,
+        ..Prefs::default()
+    };
+    let fs_prefs = FsPrefs {
+        out: Some(out_dir.to_owned()),
+        ..FsPrefs::default()
+    };
+
+    cli::dice_dir(&DIR[MONO], &fs_prefs, &prefs).unwrap();
+    assert!(Path::new(&format!("{}/atlas_0_mip_1.png", out_dir.to_str().unwrap())).exists());
+    assert!(Path::new(&format!("{}/atlas_0_mip_2.png", out_dir.to_str().unwrap())).exists());
+    assert_repro(MONO, build_arts(&out_dir, &fs_prefs), &prefs);
+    fs::remove_dir_all(out_dir).unwrap();
+}
+
+#[test]
+fn can_write_stats() {
+    let out_dir = create_temp_dir();
+
+    let prefs = Prefs {
+        unit_size: 1,
+        padding: 0,
+        collect_stats: true,
+        ..Prefs::default()
+    };
+    let fs_prefs = FsPrefs {
+        out: Some(out_dir.to_owned()),
+        ..FsPrefs::default()
+    };
+
+    let stats = cli::dice_dir(&DIR[MONO], &fs_prefs, &prefs)
+        .unwrap()
+        .unwrap();
+    let json = fs::read_to_string(format!("{}/stats.json", out_dir.to_str().unwrap())).unwrap();
+    let json = serde_json::from_str::<Value>(&json).unwrap();
+    assert_eq!(
+        json["sprites"].as_array().unwrap().len(),
+        stats.sprites.len()
+    );
+    assert_eq!(
+        json["sourceBytes"].as_u64().unwrap() as usize,
+        stats.source_bytes
+    );
+    assert_eq!(
+        json["atlasBytes"].as_u64().unwrap() as usize,
+        stats.atlas_bytes
+    );
+    fs::remove_dir_all(out_dir).unwrap();
+}
+
+#[test]
+fn doesnt_write_stats_by_default() {
+    let out_dir = create_temp_dir();
+
+    let prefs = Prefs {
+        unit_size: 1,
+        padding: 0,
+        ..Prefs::default()
+    };
+    let fs_prefs = FsPrefs {
+        out: Some(out_dir.to_owned()),
+        ..FsPrefs::default()
+    };
+
+    assert!(cli::dice_dir(&DIR[MONO], &fs_prefs, &prefs)
+        .unwrap()
+        .is_none());
+    assert!(!Path::new(&format!("{}/stats.json", out_dir.to_str().unwrap())).exists());
+    fs::remove_dir_all(out_dir).unwrap();
+}
+
+#[test]
+fn preserves_sprite_metadata() {
+    let src_dir = create_temp_dir();
+    let out_dir = create_temp_dir();
+    for name in ["red", "blue"] {
+        let file = format!("{name}.png");
+        fs::copy(DIR[MONO].join(&file), src_dir.join(&file)).unwrap();
+    }
+    fs::write(
+        src_dir.join("red.metadata"),
+        "# tags\ntag = \"hero\"\nlayer=2\n",
+    )
+    .unwrap();
+
+    let prefs = Prefs::default();
+    let fs_prefs = FsPrefs {
+        out: Some(out_dir.to_owned()),
+        ..FsPrefs::default()
+    };
+
+    cli::dice_dir(&src_dir, &fs_prefs, &prefs).unwrap();
+    let arts = build_arts(&out_dir, &fs_prefs);
+    let red = arts.sprites.iter().find(|s| s.id == "red").unwrap();
+    let blue = arts.sprites.iter().find(|s| s.id == "blue").unwrap();
+    assert_eq!(red.metadata.len(), 2);
+    assert_eq!(red.metadata["tag"], "\"hero\"");
+    assert_eq!(red.metadata["layer"], "2");
+    assert!(blue.metadata.is_empty());
+    fs::remove_dir_all(src_dir).unwrap();
+    fs::remove_dir_all(out_dir).unwrap();
+}
+
+#[test]
+fn errs_on_malformed_metadata() {
+    let src_dir = create_temp_dir();
+    fs::copy(DIR[MONO].join("red.png"), src_dir.join("red.png")).unwrap();
+    fs::write(src_dir.join("red.metadata"), "tag\n").unwrap();
+    assert!(
+        cli::dice_dir(&src_dir, &FsPrefs::default(), &Prefs::default())
+            .is_err_and(|e| e.to_string().contains("Invalid line 1"))
+    );
+    fs::remove_dir_all(src_dir).unwrap();
+}
+
 fn build_arts(dir: &Path, prefs: &FsPrefs) -> Artifacts {
     let ext = prefs.atlas_format.extension();
     let atlas_img = image::open(format!("{}/atlas_0.{ext}", dir.to_str().unwrap())).unwrap();
@@ -133,7 +258,13 @@ fn build_arts(dir: &Path, prefs: &FsPrefs) -> Artifacts {
         .map(parse_diced_sprite)
         .collect();
 
-    Artifacts { atlases, sprites }
+    Artifacts {
+        atlases,
+        mips: vec![],
+        cells: vec![],
+        sprites,
+        stats: None,
+    }
 }
 
 fn parse_diced_sprite(json: &Value) -> DicedSprite {
@@ -144,10 +275,7 @@ fn parse_diced_sprite(json: &Value) -> DicedSprite {
             .as_array()
             .unwrap()
             .iter()
-            .map(|v| Vertex {
-                x: v["x"].as_f64().unwrap() as f32,
-                y: v["y"].as_f64().unwrap() as f32,
-            })
+            .map(parse_vertex)
             .collect::<Vec<_>>(),
         uvs: json["uvs"]
             .as_array()
@@ -164,6 +292,8 @@ fn parse_diced_sprite(json: &Value) -> DicedSprite {
             .iter()
             .map(|v| v.as_u64().unwrap() as usize)
             .collect::<Vec<_>>(),
+        opaque_indices: parse_range(&json["opaqueIndices"]),
+        translucent_indices: parse_range(&json["translucentIndices"]),
         rect: Rect {
             x: json["rect"]["x"].as_f64().unwrap() as f32,
             y: json["rect"]["y"].as_f64().unwrap() as f32,
@@ -171,17 +301,92 @@ fn parse_diced_sprite(json: &Value) -> DicedSprite {
             height: json["rect"]["height"].as_f64().unwrap() as f32,
         },
         pivot: Pivot::new(0.5, 0.5),
+        source_size: USize {
+            width: json["sourceSize"]["width"].as_u64().unwrap() as u32,
+            height: json["sourceSize"]["height"].as_u64().unwrap() as u32,
+        },
+        pixel_rect: URect {
+            x: json["pixelRect"]["x"].as_u64().unwrap() as u32,
+            y: json["pixelRect"]["y"].as_u64().unwrap() as u32,
+            width: json["pixelRect"]["width"].as_u64().unwrap() as u32,
+            height: json["pixelRect"]["height"].as_u64().unwrap() as u32,
+        },
+        pixel_pivot: Pivot {
+            x: json["pixelPivot"]["x"].as_f64().unwrap() as f32,
+            y: json["pixelPivot"]["y"].as_f64().unwrap() as f32,
+        },
+        metadata: json["metadata"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(k, v)| (k.to_owned(), v.as_str().unwrap().to_owned()))
+            .collect(),
+        border: json["border"].as_object().map(|b| {
+            let side = |key: &str| b[key].as_u64().unwrap() as u32;
+            Border::new(side("left"), side("right"), side("top"), side("bottom"))
+        }),
+        slices: json["slices"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| parse_slice(s.as_str().unwrap()))
+            .collect(),
+        outlines: json["outlines"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|o| o.as_array().unwrap().iter().map(parse_vertex).collect())
+            .collect(),
+        colors: json["colors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| {
+                let channel = |key: &str| c[key].as_u64().unwrap() as u8;
+                Pixel::new(channel("r"), channel("g"), channel("b"), channel("a"))
+            })
+            .collect(),
+        atlas_indices: vec![],
+        alias_of: json["aliasOf"].as_str().map(str::to_owned),
+    }
+}
+
+fn parse_vertex(json: &Value) -> Vertex {
+    Vertex {
+        x: json["x"].as_f64().unwrap() as f32,
+        y: json["y"].as_f64().unwrap() as f32,
     }
 }
 
+fn parse_slice(slice: &str) -> Slice {
+    match slice {
+        "topLeft" => Slice::TopLeft,
+        "top" => Slice::Top,
+        "topRight" => Slice::TopRight,
+        "left" => Slice::Left,
+        "center" => Slice::Center,
+        "right" => Slice::Right,
+        "bottomLeft" => Slice::BottomLeft,
+        "bottom" => Slice::Bottom,
+        "bottomRight" => Slice::BottomRight,
+        _ => panic!("Unknown slice '{slice}'."),
+    }
+}
+
+fn parse_range(json: &Value) -> Range<usize> {
+    let start = json["start"].as_u64().unwrap() as usize;
+    let end = json["end"].as_u64().unwrap() as usize;
+    start..end
+}
+
 fn create_temp_dir() -> PathBuf {
     let rand: String = rand::thread_rng()
         .sample_iter(&Alphanumeric)
         .take(8)
         .map(char::from)
         .collect();
-    let crate_dir = env!("CARGO_MANIFEST_DIR");
-    let tmp_dir = PathBuf::from_str(&format!("{crate_dir}/tmp-{rand}")).unwrap();
+    // Outside the crate, so that the dirs left by the failed tests don't end up in the repo.
+    let tmp_dir = std::env::temp_dir().join(format!("sprite-dicing-{rand}"));
     _ = fs::remove_dir_all(&tmp_dir);
     fs::create_dir(&tmp_dir).unwrap();
     tmp_dir
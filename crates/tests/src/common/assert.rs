@@ -4,10 +4,7 @@ use sprite_dicing::{Artifacts, DicedSprite, Prefs};
 
 /// Asserts source sprites under specified fixture can be reproduced using specified artifacts.
 pub fn assert_repro(fixture: &str, arts: Artifacts, prefs: &Prefs) {
-    assert_eq!(
-        arts.sprites.len(), // All-transparent (clear) sources are ignored.
-        RAW[fixture].iter().filter(|(_, i)| !is_clear(i)).count()
-    );
+    assert_verified(fixture, &arts, prefs);
     let atlases: Vec<_> = arts.atlases.iter().map(from_texture).collect();
     // atlases[0].save(format!("D:/{fixture}.png",)).unwrap();
     for source in SRC[fixture].iter() {
@@ -17,27 +14,40 @@ pub fn assert_repro(fixture: &str, arts: Artifacts, prefs: &Prefs) {
         }
         let diced = arts.sprites.iter().find(|&d| d.id == source.id).unwrap();
         let atlas = &atlases[diced.atlas_index];
-        let reproduced = &reproduce(diced, atlas, prefs);
+        let reproduced = &reproduce(arts.resolve(diced), atlas);
         assert_eq!(source_raw, reproduced);
     }
 }
 
-fn reproduce(diced: &DicedSprite, atlas: &RgbaImage, prefs: &Prefs) -> RgbaImage {
-    let sprite_width = (diced.rect.width * prefs.ppu) as u32;
-    let sprite_height = (diced.rect.height * prefs.ppu) as u32;
-    let mut img = ImageBuffer::new(sprite_width, sprite_height);
+/// Asserts source sprites under specified fixture pass the library's own verification; use
+/// instead of [assert_repro] for meshes not composed of unit quads.
+pub fn assert_verified(fixture: &str, arts: &Artifacts, prefs: &Prefs) {
+    assert_eq!(
+        arts.sprites.len(), // All-transparent (clear) sources are ignored.
+        RAW[fixture].iter().filter(|(_, i)| !is_clear(i)).count()
+    );
+    let mismatches = sprite_dicing::verify(&SRC[fixture], arts, prefs);
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+}
+
+fn reproduce(diced: &DicedSprite, atlas: &RgbaImage) -> RgbaImage {
+    let size = &diced.source_size;
+    let mut img = ImageBuffer::new(size.width, size.height);
+    // PPU may be overridden per sprite, so evaluating it from the sprite rect; pixel rect
+    // is offset from the top-left corner of the source texture when trimmed.
+    let ppu = diced.pixel_rect.width as f32 / diced.rect.width;
+    let quad_offset_x = diced.pixel_rect.x as f32 + diced.pixel_pivot.x;
+    let quad_offset_y = diced.pixel_rect.y as f32 + diced.pixel_pivot.y;
     for (idx, _) in diced.vertices.iter().enumerate().step_by(4) {
         // Vertices layout by index:
         // min -> [0] [3]
         //        [1] [2] <- max
         let min_vertex = &diced.vertices[idx];
         let max_vertex = &diced.vertices[idx + 2];
-        let quad_offset_x = prefs.pivot.x * sprite_width as f32;
-        let quad_offset_y = prefs.pivot.y * sprite_height as f32;
-        let quad_min_x = (min_vertex.x * prefs.ppu + quad_offset_x) as u32;
-        let quad_min_y = (min_vertex.y * prefs.ppu + quad_offset_y) as u32;
-        let quad_max_x = (max_vertex.x * prefs.ppu + quad_offset_x) as u32;
-        let quad_max_y = (max_vertex.y * prefs.ppu + quad_offset_y) as u32;
+        let quad_min_x = (min_vertex.x * ppu + quad_offset_x).round() as u32;
+        let quad_min_y = (min_vertex.y * ppu + quad_offset_y).round() as u32;
+        let quad_max_x = (max_vertex.x * ppu + quad_offset_x).round() as u32;
+        let quad_max_y = (max_vertex.y * ppu + quad_offset_y).round() as u32;
 
         // Offsetting UVs to the center of each pixel to get the integer index on round.
         let min_uv = &diced.uvs[idx];
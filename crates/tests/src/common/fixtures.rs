@@ -13,6 +13,7 @@ pub const TRIM: &str = "trim";
 pub const NESTED: &str = "nested";
 pub const EXOTIC: &str = "exotic";
 pub const INVALID: &str = "invalid";
+pub const GRADIENT: &str = "gradient";
 
 pub static SRC: LazyLock<SourcesByFixture> = LazyLock::new(cache_sources);
 pub static DIR: LazyLock<DirByFixture> = LazyLock::new(cache_dirs);
@@ -62,11 +63,7 @@ fn cache_raws() -> RawsByFixture {
 }
 
 fn create_sprite(path: &Path, image: RgbaImage, root: &Path) -> SourceSprite {
-    SourceSprite {
-        id: build_id(path, root),
-        texture: img::to_texture(&image),
-        pivot: None,
-    }
+    SourceSprite::new(build_id(path, root), img::to_texture(&image))
 }
 
 fn build_id(path: &Path, root: &Path) -> String {
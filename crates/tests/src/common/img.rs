@@ -1,7 +1,8 @@
-use image::RgbaImage;
+use image::{ImageFormat, RgbaImage};
 use sprite_dicing::{Pixel, Texture};
 use std::collections::HashMap;
 use std::fs;
+use std::io::Cursor;
 use std::path::{Path, PathBuf};
 
 pub fn load_all<T>(dir: &T) -> HashMap<PathBuf, RgbaImage>
@@ -40,6 +41,14 @@ pub fn from_texture(tex: &Texture) -> RgbaImage {
     RgbaImage::from_raw(tex.width, tex.height, buf).unwrap()
 }
 
+pub fn encode_png(tex: &Texture) -> Vec<u8> {
+    let mut buf = Cursor::new(Vec::new());
+    from_texture(tex)
+        .write_to(&mut buf, ImageFormat::Png)
+        .unwrap();
+    buf.into_inner()
+}
+
 pub fn is_clear(img: &RgbaImage) -> bool {
     img.pixels().all(|p| p.0[3] == 0)
 }
@@ -1,7 +1,8 @@
 //! End-to-end tests of the core library.
 
 use crate::common::*;
-use sprite_dicing::Prefs;
+use sprite_dicing::{AlphaCrop, Border, Pivot, Pixel, Prefs, Search, SourceSprite, UnitOrder};
+use std::collections::HashMap;
 
 #[test]
 fn mono_1x_reproduced() {
@@ -52,7 +53,7 @@ fn trim_1x_reproduced() {
     let prefs = Prefs {
         unit_size: 1,
         padding: 0,
-        trim_transparent: false, // TODO: Make repro assert work with trimming.
+        trim_transparent: false,
         ..Prefs::default()
     };
     let diced = sprite_dicing::dice(&SRC[TRIM], &prefs).unwrap();
@@ -83,6 +84,65 @@ fn trim_2x_with_padding_reproduced() {
     assert_repro(TRIM, diced, &prefs);
 }
 
+#[test]
+fn trim_1x_trimmed_reproduced() {
+    let prefs = Prefs {
+        unit_size: 1,
+        padding: 0,
+        trim_transparent: true,
+        ..Prefs::default()
+    };
+    let diced = sprite_dicing::dice(&SRC[TRIM], &prefs).unwrap();
+    assert_repro(TRIM, diced, &prefs);
+}
+
+#[test]
+fn trim_2x_trimmed_reproduced() {
+    let prefs = Prefs {
+        unit_size: 2,
+        padding: 0,
+        trim_transparent: true,
+        ..Prefs::default()
+    };
+    let diced = sprite_dicing::dice(&SRC[TRIM], &prefs).unwrap();
+    assert_repro(TRIM, diced, &prefs);
+}
+
+#[test]
+fn trim_2x_with_padding_trimmed_reproduced() {
+    let prefs = Prefs {
+        unit_size: 2,
+        padding: 2,
+        trim_transparent: true,
+        ..Prefs::default()
+    };
+    let diced = sprite_dicing::dice(&SRC[TRIM], &prefs).unwrap();
+    assert_repro(TRIM, diced, &prefs);
+}
+
+#[test]
+fn sequence_frames_are_aligned() {
+    let prefs = Prefs {
+        unit_size: 2,
+        trim_transparent: true,
+        ..Prefs::default()
+    };
+    let mut sources = SRC[TRIM].to_owned();
+    for source in sources.iter_mut() {
+        source.sequence = Some("trim".to_owned());
+    }
+    sources[0].pivot = Some(Pivot::new(0.25, 0.75));
+    let diced = sprite_dicing::dice(&sources, &prefs).unwrap();
+    let first = &diced.sprites[0];
+    for sprite in diced.sprites.iter() {
+        assert_eq!(sprite.pixel_rect, first.pixel_rect);
+        assert_eq!(sprite.rect, first.rect);
+        assert_eq!(sprite.pivot, Pivot::new(0.25, 0.75));
+    }
+    let mismatches = sprite_dicing::verify(&sources, &diced, &prefs);
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+}
+
 #[test]
 fn icons_reproduced() {
     let prefs = Prefs {
@@ -94,6 +154,351 @@ fn icons_reproduced() {
     assert_repro(ICONS, diced, &prefs);
 }
 
+#[test]
+fn icons_trimmed_reproduced() {
+    let prefs = Prefs::default();
+    let diced = sprite_dicing::dice(&SRC[ICONS], &prefs).unwrap();
+    assert_repro(ICONS, diced, &prefs);
+}
+
+#[test]
+fn alpha_cropped_reproduced() {
+    for alpha_crop in [AlphaCrop::Rect, AlphaCrop::Polygon] {
+        let prefs = Prefs {
+            unit_size: 8,
+            padding: 2,
+            trim_transparent: true,
+            alpha_crop,
+            ..Prefs::default()
+        };
+        let estimate = sprite_dicing::estimate(&SRC[ICONS], &prefs).unwrap();
+        let diced = sprite_dicing::dice(&SRC[ICONS], &prefs).unwrap();
+        let vertices = diced
+            .sprites
+            .iter()
+            .map(|s| s.vertices.len())
+            .sum::<usize>();
+        let indices = diced.sprites.iter().map(|s| s.indices.len()).sum::<usize>();
+        assert_eq!((estimate.vertices, estimate.indices), (vertices, indices));
+        match alpha_crop {
+            AlphaCrop::Polygon => assert_verified(ICONS, &diced, &prefs),
+            _ => assert_repro(ICONS, diced, &prefs),
+        }
+    }
+}
+
+#[test]
+fn opaque_faces_are_split_from_translucent() {
+    let prefs = Prefs {
+        unit_size: 8,
+        ..Prefs::default()
+    };
+    let diced = sprite_dicing::dice(&SRC[ICONS], &prefs).unwrap();
+    for sprite in diced.sprites.iter() {
+        assert_eq!(sprite.opaque_indices.start, 0);
+        assert_eq!(sprite.opaque_indices.end, sprite.translucent_indices.start);
+        assert_eq!(sprite.translucent_indices.end, sprite.indices.len());
+    }
+    assert!(diced.sprites.iter().any(|s| !s.opaque_indices.is_empty()));
+    assert!(diced
+        .sprites
+        .iter()
+        .any(|s| !s.translucent_indices.is_empty()));
+    assert_repro(ICONS, diced, &prefs);
+}
+
+#[test]
+fn outlines_are_bounded_by_sprite_mesh() {
+    let count = |tolerance| {
+        let prefs = Prefs {
+            unit_size: 8,
+            trim_transparent: true,
+            outline_tolerance: Some(tolerance),
+            ..Prefs::default()
+        };
+        let diced = sprite_dicing::dice(&SRC[ICONS], &prefs).unwrap();
+        for sprite in diced.sprites.iter() {
+            let xs = sprite.vertices.iter().map(|v| v.x);
+            let ys = sprite.vertices.iter().map(|v| v.y);
+            let (min_x, max_x) = (
+                xs.clone().fold(f32::MAX, f32::min),
+                xs.fold(f32::MIN, f32::max),
+            );
+            let (min_y, max_y) = (
+                ys.clone().fold(f32::MAX, f32::min),
+                ys.fold(f32::MIN, f32::max),
+            );
+            assert!(!sprite.outlines.is_empty());
+            assert!(sprite.outlines.iter().flatten().all(|v| {
+                v.x >= min_x - 1e-4
+                    && v.y >= min_y - 1e-4
+                    && v.x <= max_x + 1e-4
+                    && v.y <= max_y + 1e-4
+            }));
+        }
+        diced
+            .sprites
+            .iter()
+            .flat_map(|s| s.outlines.iter())
+            .flatten()
+            .count()
+    };
+    assert!(count(2.0) < count(0.0));
+}
+
+#[test]
+fn overridden_reproduced() {
+    let prefs = Prefs {
+        unit_size: 8,
+        padding: 2,
+        ..Prefs::default()
+    };
+    let sources = SRC[ICONS]
+        .iter()
+        .enumerate()
+        .map(|(idx, source)| {
+            let mut source = source.to_owned();
+            source.ppu = Some(if idx % 2 == 0 { 32.0 } else { 100.0 });
+            source.trim_transparent = Some(idx % 3 != 0);
+            source.padding = Some(idx as u32 % 3);
+            source
+        })
+        .collect::<Vec<_>>();
+    let diced = sprite_dicing::dice(&sources, &prefs).unwrap();
+    for sprite in diced.sprites.iter() {
+        let source = sources.iter().find(|s| s.id == sprite.id).unwrap();
+        let ppu = source.ppu.unwrap();
+        assert!((sprite.rect.width * ppu - sprite.pixel_rect.width as f32).abs() < 1e-3);
+    }
+    assert_repro(ICONS, diced, &prefs);
+}
+
+#[test]
+fn tinted_copies_reuse_units() {
+    let prefs = Prefs {
+        unit_size: 8,
+        dedupe_tints: true,
+        ..Prefs::default()
+    };
+    let tint = |p: &Pixel| {
+        let [r, g, b, a] = p.to_raw();
+        Pixel::new(r / 2, g, (b as u32 * 3 / 4) as u8, a)
+    };
+    let mut sources = SRC[ICONS].to_owned();
+    for source in SRC[ICONS].iter() {
+        let mut copy = source.to_owned();
+        copy.id = format!("{}-tinted", source.id);
+        copy.texture.pixels = source.texture.pixels.iter().map(tint).collect();
+        sources.push(copy);
+    }
+    let base = sprite_dicing::estimate(&SRC[ICONS], &prefs).unwrap();
+    let estimate = sprite_dicing::estimate(&sources, &prefs).unwrap();
+    assert_eq!(estimate.unique_units, base.unique_units);
+    let diced = sprite_dicing::dice(&sources, &prefs).unwrap();
+    assert!(diced
+        .sprites
+        .iter()
+        .all(|s| s.colors.len() == s.vertices.len()));
+    let mismatches = sprite_dicing::verify(&sources, &diced, &prefs);
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+}
+
+#[test]
+fn duplicates_are_aliased() {
+    let prefs = Prefs {
+        unit_size: 8,
+        alias_duplicates: true,
+        ..Prefs::default()
+    };
+    let mut sources = SRC[ICONS].to_owned();
+    for source in SRC[ICONS].iter() {
+        let mut copy = source.to_owned();
+        copy.id = format!("{}-copy", source.id);
+        sources.push(copy);
+    }
+    let base = sprite_dicing::estimate(&SRC[ICONS], &prefs).unwrap();
+    let estimate = sprite_dicing::estimate(&sources, &prefs).unwrap();
+    assert_eq!(estimate.vertices, base.vertices);
+    let diced = sprite_dicing::dice(&sources, &prefs).unwrap();
+    for source in SRC[ICONS].iter() {
+        let find = |id: &str| diced.sprites.iter().find(|s| s.id == id).unwrap();
+        let (original, copy) = (find(&source.id), find(&format!("{}-copy", source.id)));
+        let (original, alias) = match copy.alias_of {
+            Some(_) => (original, copy),
+            None => (copy, original),
+        };
+        assert_eq!(alias.alias_of.as_ref(), Some(&original.id));
+        assert!(alias.vertices.is_empty());
+        assert_eq!(diced.resolve(alias).id, original.id);
+    }
+    let mismatches = sprite_dicing::verify(&sources, &diced, &prefs);
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+}
+
+#[test]
+fn incremental_dicing_changes_only_affected_sprites() {
+    let prefs = Prefs {
+        unit_size: 8,
+        ..Prefs::default()
+    };
+    let sources = &SRC[ICONS];
+    let (first, last) = (&sources[0].id, &sources[sources.len() - 1].id);
+    let prev = sprite_dicing::dice(&sources[..sources.len() - 1], &prefs).unwrap();
+
+    let same = &sources[..sources.len() - 1];
+    let same = sprite_dicing::dice_incremental(same, &prev, &prefs).unwrap();
+    assert!(same.changed_sprites.is_empty());
+    assert!(same.removed_sprites.is_empty());
+    assert!(same.changed_atlases.is_empty());
+
+    let added = sprite_dicing::dice_incremental(sources, &prev, &prefs).unwrap();
+    assert_eq!(&added.changed_sprites, &[last.to_owned()]);
+    assert!(added.removed_sprites.is_empty());
+    let mismatches = sprite_dicing::verify(sources, &added.artifacts, &prefs);
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+
+    let rest = &sources[1..sources.len() - 1];
+    let removed = sprite_dicing::dice_incremental(rest, &prev, &prefs).unwrap();
+    assert_eq!(&removed.removed_sprites, &[first.to_owned()]);
+    assert!(removed.changed_sprites.is_empty());
+    let mismatches = sprite_dicing::verify(rest, &removed.artifacts, &prefs);
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+}
+
+#[test]
+fn incremental_dicing_replaces_cells_with_mismatched_content() {
+    let prefs = Prefs {
+        unit_size: 8,
+        ..Prefs::default()
+    };
+    let sources = &SRC[ICONS];
+    let mut prev = sprite_dicing::dice(sources, &prefs).unwrap();
+    let rect = prev.cells[0][0].rect.to_owned();
+    let width = prev.atlases[0].width;
+    let pixel = &mut prev.atlases[0].pixels[(rect.x + rect.y * width) as usize];
+    let [r, g, b, a] = pixel.to_raw();
+    *pixel = Pixel::new(255 - r, 255 - g, 255 - b, 255 - a);
+
+    let diced = sprite_dicing::dice_incremental(sources, &prev, &prefs).unwrap();
+    assert_eq!(diced.changed_atlases, [0]);
+    let mismatches = sprite_dicing::verify(sources, &diced.artifacts, &prefs);
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+}
+
+#[test]
+fn incremental_dicing_changes_aliases_of_changed_sprites() {
+    let prefs = Prefs {
+        unit_size: 8,
+        alias_duplicates: true,
+        ..Prefs::default()
+    };
+    let source = &SRC[ICONS][0];
+    let copy = SourceSprite {
+        id: format!("{}-copy", source.id),
+        ..source.to_owned()
+    };
+    let sources = [source.to_owned(), copy.to_owned()];
+    let first = sprite_dicing::dice(&sources, &prefs).unwrap();
+    // Incremental dicing builds the sprites in order of the sources, so the copy is the alias.
+    let prev = sprite_dicing::dice_incremental(&sources, &first, &prefs).unwrap();
+    let prev = prev.artifacts;
+    let alias = prev.sprites.iter().find(|s| s.id == copy.id).unwrap();
+    assert_eq!(alias.alias_of.as_ref(), Some(&source.id));
+
+    let invert = |s: &SourceSprite| {
+        let mut s = s.to_owned();
+        for pixel in s.texture.pixels.iter_mut() {
+            let [r, g, b, a] = pixel.to_raw();
+            *pixel = Pixel::new(255 - r, 255 - g, 255 - b, a);
+        }
+        s
+    };
+    let sources = [invert(source), invert(&copy)];
+    let inverted = sprite_dicing::dice_incremental(&sources, &prev, &prefs).unwrap();
+    let find = |id: &str| inverted.artifacts.sprites.iter().find(|s| s.id == id);
+    assert_eq!(find(&alias.id), Some(alias));
+    let mut changed = inverted.changed_sprites.to_owned();
+    changed.sort();
+    assert_eq!(changed, vec![source.id.to_owned(), copy.id.to_owned()]);
+}
+
+#[test]
+fn new_content_reuses_base_atlases() {
+    let prefs = Prefs {
+        unit_size: 8,
+        ..Prefs::default()
+    };
+    let sources = &SRC[ICONS];
+    let base = sprite_dicing::dice(&sources[..sources.len() - 1], &prefs).unwrap();
+    let mut patch = vec![sources[sources.len() - 1].to_owned()];
+    for source in sources[..2].iter() {
+        let mut copy = source.to_owned();
+        copy.id = format!("{}-dlc", source.id);
+        patch.push(copy);
+    }
+    let diced = sprite_dicing::dice_with_base(&patch, &base, &prefs).unwrap();
+    assert_eq!(diced.atlases[..base.atlases.len()], base.atlases[..]);
+    assert_eq!(diced.atlases.len(), base.atlases.len() + 1);
+    for sprite in diced.sprites.iter() {
+        let in_base = sprite.atlas_index < base.atlases.len();
+        assert_eq!(in_base, sprite.id.ends_with("-dlc"));
+    }
+    let mismatches = sprite_dicing::verify(&patch, &diced, &prefs);
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+}
+
+#[test]
+fn mixed_content_spans_base_and_new_atlases() {
+    let prefs = Prefs {
+        unit_size: 8,
+        ..Prefs::default()
+    };
+    let base = sprite_dicing::dice(&SRC[ICONS], &prefs).unwrap();
+    let mut patch = SRC[ICONS][0].to_owned();
+    patch.id = format!("{}-dlc", patch.id);
+    let marked = patch
+        .texture
+        .pixels
+        .iter()
+        .position(|p| p.a() == 255)
+        .unwrap();
+    patch.texture.pixels[marked] = Pixel::new(1, 2, 3, 255);
+    let patch = [patch];
+    let diced = sprite_dicing::dice_with_base(&patch, &base, &prefs).unwrap();
+    assert_eq!(diced.atlases.len(), base.atlases.len() + 1);
+    assert_eq!(diced.cells[base.atlases.len()].len(), 1);
+    let sprite = &diced.sprites[0];
+    assert_eq!(sprite.atlas_index, base.atlases.len());
+    assert_eq!(sprite.atlas_indices.len(), sprite.vertices.len());
+    assert!(sprite.atlas_indices.contains(&sprite.atlas_index));
+    assert!(sprite
+        .atlas_indices
+        .iter()
+        .any(|idx| *idx < base.atlases.len()));
+    let mismatches = sprite_dicing::verify(&patch, &diced, &prefs);
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+}
+
+#[test]
+fn sliced_reproduced() {
+    let prefs = Prefs {
+        unit_size: 16,
+        padding: 2,
+        ..Prefs::default()
+    };
+    let mut sources = SRC[ICONS].to_owned();
+    for src in sources.iter_mut() {
+        let (w, h) = (src.texture.width, src.texture.height);
+        src.border = Some(Border::new(w / 3, w / 5, h / 7, h / 4));
+    }
+    let diced = sprite_dicing::dice(&sources, &prefs).unwrap();
+    assert!(diced
+        .sprites
+        .iter()
+        .all(|s| s.slices.len() == s.vertices.len()));
+    assert_repro(ICONS, diced, &prefs);
+}
+
 #[test]
 fn atlas_not_square_when_not_forced() {
     let prefs = Prefs {
@@ -160,3 +565,233 @@ fn multiple_atlases_when_limited() {
     assert_eq!(diced.atlases.len(), 2);
     assert_repro(MONO, diced, &prefs);
 }
+
+#[test]
+fn source_order_reproduced_and_compressed_better_than_hash() {
+    let prefs = Prefs {
+        unit_size: 8,
+        padding: 0,
+        unit_order: UnitOrder::Source,
+        ..Prefs::default()
+    };
+    let diced = sprite_dicing::dice(&SRC[GRADIENT], &prefs).unwrap();
+    assert!(encode_png(&diced.atlases[0]).len() < encode_hash_ordered(GRADIENT));
+    assert_repro(GRADIENT, diced, &prefs);
+}
+
+#[test]
+fn color_order_reproduced_and_compressed_better_than_hash() {
+    let prefs = Prefs {
+        unit_size: 8,
+        padding: 0,
+        unit_order: UnitOrder::Color,
+        ..Prefs::default()
+    };
+    let diced = sprite_dicing::dice(&SRC[GRADIENT], &prefs).unwrap();
+    assert!(encode_png(&diced.atlases[0]).len() < encode_hash_ordered(GRADIENT));
+    assert_repro(GRADIENT, diced, &prefs);
+}
+
+fn encode_hash_ordered(fixture: &str) -> usize {
+    let prefs = Prefs {
+        unit_size: 8,
+        padding: 0,
+        unit_order: UnitOrder::Hash,
+        ..Prefs::default()
+    };
+    let diced = sprite_dicing::dice(&SRC[fixture], &prefs).unwrap();
+    encode_png(&diced.atlases[0]).len()
+}
+
+#[test]
+fn block_aligned_reproduced() {
+    let prefs = Prefs {
+        unit_size: 3,
+        padding: 1,
+        block_alignment: 4,
+        grow_padding: false,
+        ppu: 1.0,
+        trim_transparent: false,
+        ..Prefs::default()
+    };
+    let diced = sprite_dicing::dice(&SRC[ICONS], &prefs).unwrap();
+    assert_eq!(diced.atlases[0].width % 4, 0);
+    assert_eq!(diced.atlases[0].height % 4, 0);
+    assert_repro(ICONS, diced, &prefs);
+}
+
+#[test]
+fn block_aligned_with_grown_padding_reproduced() {
+    let prefs = Prefs {
+        unit_size: 3,
+        padding: 1,
+        block_alignment: 8,
+        grow_padding: true,
+        ppu: 1.0,
+        trim_transparent: false,
+        ..Prefs::default()
+    };
+    let diced = sprite_dicing::dice(&SRC[ICONS], &prefs).unwrap();
+    assert_eq!(diced.atlases[0].width % 8, 0);
+    assert_eq!(diced.atlases[0].height % 8, 0);
+    assert_repro(ICONS, diced, &prefs);
+}
+
+#[test]
+fn mip_aligned_reproduced() {
+    let prefs = Prefs {
+        unit_size: 3,
+        padding: 1,
+        mip_levels: 3,
+        grow_padding: true,
+        ppu: 1.0,
+        trim_transparent: false,
+        ..Prefs::default()
+    };
+    let diced = sprite_dicing::dice(&SRC[ICONS], &prefs).unwrap();
+    assert_eq!(diced.mips[0].len(), 3);
+    assert_eq!(diced.mips[0][2].width, diced.atlases[0].width / 8);
+    assert_eq!(diced.mips[0][2].height, diced.atlases[0].height / 8);
+    assert_repro(ICONS, diced, &prefs);
+}
+
+#[test]
+fn estimate_matches_diced() {
+    let prefs = Prefs {
+        unit_size: 8,
+        padding: 1,
+        atlas_size_limit: 256,
+        ..Prefs::default()
+    };
+    let estimate = sprite_dicing::estimate(&SRC[ICONS], &prefs).unwrap();
+    let diced = sprite_dicing::dice(&SRC[ICONS], &prefs).unwrap();
+    assert_eq!(estimate.atlases.len(), diced.atlases.len());
+    for (size, atlas) in estimate.atlases.iter().zip(diced.atlases.iter()) {
+        assert_eq!((size.width, size.height), (atlas.width, atlas.height));
+    }
+    let atlas_bytes = diced
+        .atlases
+        .iter()
+        .map(|a| a.pixels.len() * 4)
+        .sum::<usize>();
+    assert_eq!(estimate.atlas_bytes, atlas_bytes);
+    assert_eq!(
+        estimate.vertices,
+        diced
+            .sprites
+            .iter()
+            .map(|s| s.vertices.len())
+            .sum::<usize>()
+    );
+    assert_eq!(
+        estimate.indices,
+        diced.sprites.iter().map(|s| s.indices.len()).sum::<usize>()
+    );
+    assert!(estimate.mesh_bytes > 0);
+}
+
+#[test]
+fn estimate_counts_unique_units() {
+    let prefs = Prefs {
+        unit_size: 1,
+        padding: 0,
+        ..Prefs::default()
+    };
+    let estimate = sprite_dicing::estimate(&SRC[MONO], &prefs).unwrap();
+    // 8 opaque mono-colored sprites + clear one, which is ignored.
+    assert_eq!(estimate.unique_units, 8);
+}
+
+#[test]
+fn estimate_errs_on_invalid_prefs() {
+    let prefs = Prefs {
+        unit_size: 0,
+        ..Prefs::default()
+    };
+    assert!(sprite_dicing::estimate(&SRC[MONO], &prefs).is_err());
+}
+
+#[test]
+fn optimized_prefs_reproduced() {
+    let prefs = Prefs {
+        ppu: 1.0,
+        trim_transparent: false,
+        ..Prefs::default()
+    };
+    let search = Search {
+        unit_sizes: vec![4, 8, 16],
+        paddings: vec![0, 1],
+        ..Search::default()
+    };
+    let optimized = sprite_dicing::optimize(&SRC[ICONS], prefs, &search).unwrap();
+    let min_cost = optimized.candidates.iter().map(|c| c.cost).reduce(f32::min);
+    let best = optimized
+        .candidates
+        .iter()
+        .find(|c| Some(c.cost) == min_cost)
+        .unwrap();
+    assert_eq!(optimized.candidates.len(), 6);
+    assert_eq!(optimized.prefs.unit_size, best.unit_size);
+    assert_eq!(optimized.prefs.padding, best.padding);
+    let diced = sprite_dicing::dice(&SRC[ICONS], &optimized.prefs).unwrap();
+    assert_repro(ICONS, diced, &optimized.prefs);
+}
+
+#[test]
+fn stats_match_diced() {
+    let prefs = Prefs {
+        unit_size: 8,
+        padding: 1,
+        atlas_size_limit: 256,
+        collect_stats: true,
+        ..Prefs::default()
+    };
+    let diced = sprite_dicing::dice(&SRC[ICONS], &prefs).unwrap();
+    let estimate = sprite_dicing::estimate(&SRC[ICONS], &prefs).unwrap();
+    let stats = diced.stats.unwrap();
+    assert_eq!(stats.sprites.len(), diced.sprites.len());
+    for (stats, sprite) in stats.sprites.iter().zip(diced.sprites.iter()) {
+        assert_eq!(stats.id, sprite.id);
+        assert_eq!(stats.vertices, sprite.vertices.len());
+        assert!(stats.unique <= stats.units);
+        assert!(stats.shared <= stats.unique);
+    }
+    // Units shared by sprites packed into different atlases are baked into each of them.
+    assert!(stats.atlases.iter().map(|a| a.units).sum::<usize>() >= estimate.unique_units);
+    assert_eq!(stats.atlas_bytes, estimate.atlas_bytes);
+    assert_eq!(stats.mesh_bytes, estimate.mesh_bytes);
+    let source_bytes = SRC[ICONS].iter().map(|s| s.texture.pixels.len() * 4);
+    assert_eq!(stats.source_bytes, source_bytes.sum::<usize>());
+    assert!(stats.compression_ratio() > 1.0);
+}
+
+#[test]
+fn cells_reference_sprite_units() {
+    let prefs = Prefs {
+        unit_size: 8,
+        padding: 1,
+        atlas_size_limit: 256,
+        trim_transparent: false,
+        ppu: 1.0,
+        ..Prefs::default()
+    };
+    let diced = sprite_dicing::dice(&SRC[ICONS], &prefs).unwrap();
+    assert_eq!(diced.cells.len(), diced.atlases.len());
+    let mut refs = HashMap::<&str, usize>::new();
+    for (atlas_idx, cells) in diced.cells.iter().enumerate() {
+        let atlas = &diced.atlases[atlas_idx];
+        for cell in cells {
+            assert!(cell.rect.x + cell.rect.width <= atlas.width);
+            assert!(cell.rect.y + cell.rect.height <= atlas.height);
+            for cell_ref in cell.refs.iter() {
+                *refs.entry(&cell_ref.sprite_id).or_default() += 1;
+                let sprite = diced.sprites.iter().find(|s| s.id == cell_ref.sprite_id);
+                assert_eq!(sprite.unwrap().atlas_index, atlas_idx);
+            }
+        }
+    }
+    // Each unit is mapped to a quad of 4 vertices.
+    for sprite in diced.sprites.iter() {
+        assert_eq!(refs[sprite.id.as_str()] * 4, sprite.vertices.len());
+    }
+}
@@ -1,6 +1,5 @@
 using System;
 using System.Collections.Generic;
-using System.IO;
 using System.Linq;
 using UnityEditor;
 using UnityEngine;
@@ -15,6 +14,7 @@ namespace SpriteDicing.Editors
         private readonly string atlasPath;
 
         private double? buildStartTime;
+        private bool canceled;
 
         public AtlasBuilder (SerializedObject serializedObject)
         {
@@ -25,13 +25,14 @@ namespace SpriteDicing.Editors
 
         public void Build ()
         {
+            canceled = false;
             try
             {
                 var sources = CollectSourceSprites();
                 using var diced = Native.Dice(sources.Select(s => s.Native), BuildPrefs());
                 var atlases = ImportAtlases(diced.Atlases);
                 BuildDicedSprites(diced.Sprites, atlases);
-                UpdateCompressionRatio(sources.Select(s => s.Texture), atlases);
+                UpdateCompressionRatio(diced.Stats.Value);
             }
             catch (Exception e)
             {
@@ -68,9 +69,12 @@ namespace SpriteDicing.Editors
             AtlasSizeLimit = (uint)AtlasSizeLimit,
             AtlasSquare = ForceSquare,
             AtlasPOT = ForcePot,
+            CollectStats = true,
             PPU = PPU,
             Pivot = new Native.Pivot { X = DefaultPivot.x, Y = DefaultPivot.y },
-            OnProgress = p => DisplayProgressBar(p.Activity, .25f + (p.Ratio / 4))
+            // Throwing inside native callbacks is not safe, so cancellation is requested instead.
+            OnProgress = p => canceled = UpdateProgressBar(p.Activity, .25f + (p.Ratio / 4)),
+            OnCancel = () => canceled
         };
 
         private Texture2D[] ImportAtlases (IReadOnlyList<Native.Texture> atlases)
@@ -135,42 +139,31 @@ namespace SpriteDicing.Editors
             new DicedSpriteSerializer(serializedObject).Serialize(sprites);
         }
 
-        private void UpdateCompressionRatio (IEnumerable<Texture2D> sources, IEnumerable<Texture2D> atlases)
+        private void UpdateCompressionRatio (Native.Stats stats)
         {
             AssetDatabase.SaveAssets();
-            var sourceSize = sources.Sum(GetAssetSize);
-            var atlasSize = atlases.Sum(GetAssetSize);
-            var dataSize = GetDataSize();
-            var ratio = sourceSize / (float)(atlasSize + dataSize);
+            var sourceSize = stats.SourceBytes / 1024;
+            var atlasSize = stats.AtlasBytes / 1024;
+            var dataSize = stats.MeshBytes / 1024;
+            var ratio = stats.CompressionRatio;
             var color = ratio > 2 ? EditorGUIUtility.isProSkin ? "lime" : "green" : ratio > 1 ? "yellow" : "red";
             LastRatioValueProperty.stringValue = $"{sourceSize} KB / ({atlasSize} KB + {dataSize} KB) = <color={color}>{ratio:F2}</color>";
             LastRatioValueProperty.serializedObject.ApplyModifiedPropertiesWithoutUndo();
             AssetDatabase.SaveAssetIfDirty(target);
-
-            long GetAssetSize (UnityEngine.Object asset)
-            {
-                var assetPath = AssetDatabase.GetAssetPath(asset);
-                if (!File.Exists(assetPath)) return 0;
-                return new FileInfo(assetPath).Length / 1024;
-            }
-
-            long GetDataSize ()
-            {
-                var size = GetAssetSize(target);
-                if (DecoupleSpriteData)
-                    for (int i = SpritesProperty.arraySize - 1; i >= 0; i--)
-                        size += GetAssetSize(SpritesProperty.GetArrayElementAtIndex(i).objectReferenceValue);
-                return size / (EditorSettings.serializationMode == SerializationMode.ForceText ? 2 : 1);
-            }
         }
 
         private void DisplayProgressBar (string activity, float progress)
+        {
+            if (UpdateProgressBar(activity, progress))
+                throw new OperationCanceledException("Diced sprite atlas building was canceled by the user.");
+        }
+
+        private bool UpdateProgressBar (string activity, float progress)
         {
             buildStartTime ??= EditorApplication.timeSinceStartup;
             var elapsed = TimeSpan.FromSeconds(EditorApplication.timeSinceStartup - buildStartTime.Value);
             var title = $"Building Diced Atlas ({elapsed:mm\\:ss})";
-            if (EditorUtility.DisplayCancelableProgressBar(title, activity, progress))
-                throw new OperationCanceledException("Diced sprite atlas building was canceled by the user.");
+            return EditorUtility.DisplayCancelableProgressBar(title, activity, progress);
         }
     }
 }
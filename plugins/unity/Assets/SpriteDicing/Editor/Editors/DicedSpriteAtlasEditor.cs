@@ -8,7 +8,7 @@ namespace SpriteDicing.Editors
     [CustomEditor(typeof(DicedSpriteAtlas))]
     public class DicedSpriteAtlasEditor : Editor
     {
-        private static readonly GUIContent ratioContent = new("Compression Ratio", "Total size of the source textures divided by size of the generated diced atlas textures plus associated sprite data (higher is better).");
+        private static readonly GUIContent ratioContent = new("Compression Ratio", "Total size of the source textures divided by size of the generated diced atlas textures plus associated mesh data, all uncompressed (higher is better).");
         private static readonly GUIContent defaultPivotContent = new("Default Pivot", "Origin of the generated diced meshes relative to the bottom-left corner of the sprite rectangle.");
         private static readonly GUIContent keepOriginalPivotContent = new("Keep Original", "Whether to use pivot set in each individual source sprite (if any), before falling back to the default.");
         private static readonly GUIContent decoupleSpriteDataContent = new("Decouple Sprite Data", "Whether to save sprite assets in a separate folder instead of adding them as children of the atlas asset.\nWARNING: When rebuilding after changing this option the asset references to previously generated sprites will be lost.");
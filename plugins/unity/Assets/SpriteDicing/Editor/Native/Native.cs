@@ -18,6 +18,12 @@ namespace SpriteDicing
             public string Id { get; init; }
             public Texture Texture { get; init; }
             public Pivot? Pivot { get; init; }
+            public IReadOnlyDictionary<string, string> Metadata { get; init; }
+            public Border? Border { get; init; }
+            public float? PPU { get; init; }
+            public bool? TrimTransparent { get; init; }
+            public uint? Padding { get; init; }
+            public string Sequence { get; init; }
         }
 
         public readonly struct Texture
@@ -51,27 +57,84 @@ namespace SpriteDicing
         {
             public uint UnitSize { get; init; }
             public uint Padding { get; init; }
+            public uint BlockAlignment { get; init; }
+            public bool GrowPadding { get; init; }
+            public uint MipLevels { get; init; }
             public float UVInset { get; init; }
             public bool TrimTransparent { get; init; }
+            public AlphaCrop AlphaCrop { get; init; }
+            public float? OutlineTolerance { get; init; }
+            public bool DedupeTints { get; init; }
+            public bool AliasDuplicates { get; init; }
             public uint AtlasSizeLimit { get; init; }
             public bool AtlasSquare { get; init; }
             public bool AtlasPOT { get; init; }
+            public UnitOrder UnitOrder { get; init; }
+            public bool CollectStats { get; init; }
             public float PPU { get; init; }
             public Pivot Pivot { get; init; }
+            public Coordinates Coordinates { get; init; }
             public ProgressCallback OnProgress { get; init; }
+            public CancelCallback OnCancel { get; init; }
+        }
+
+        public enum AlphaCrop : byte
+        {
+            Off,
+            Rect,
+            Polygon
+        }
+
+        public enum UnitOrder : byte
+        {
+            Hash,
+            Source,
+            Color
+        }
+
+        public readonly struct Coordinates
+        {
+            public YAxis YAxis { get; init; }
+            public UVOrigin UVOrigin { get; init; }
+            public Winding Winding { get; init; }
+        }
+
+        public enum YAxis : byte
+        {
+            Down,
+            Up
+        }
+
+        public enum UVOrigin : byte
+        {
+            TopLeft,
+            BottomLeft
+        }
+
+        public enum Winding : byte
+        {
+            CounterClockwise,
+            Clockwise
         }
 
         public class Artifacts : IDisposable
         {
             public IReadOnlyList<Texture> Atlases { get; }
+            public IReadOnlyList<IReadOnlyList<Texture>> Mips { get; }
+            public IReadOnlyList<IReadOnlyList<AtlasCell>> Cells { get; }
             public IReadOnlyList<DicedSprite> Sprites { get; }
+            public Stats? Stats { get; }
 
             private readonly List<IntPtr> pts;
 
-            internal Artifacts (Texture[] atlases, DicedSprite[] sprites, List<IntPtr> pts)
+            internal Artifacts (Texture[] atlases, Texture[][] mips, AtlasCell[][] cells,
+                DicedSprite[] sprites, Stats? stats, List<IntPtr> pts)
             {
                 Atlases = atlases;
+                Mips = mips;
+                Cells = cells;
                 Sprites = sprites;
+                Stats = stats;
                 this.pts = pts;
             }
 
@@ -83,6 +146,49 @@ namespace SpriteDicing
             }
         }
 
+        public readonly struct AtlasCell
+        {
+            public ulong Hash { get; init; }
+            public URect Rect { get; init; }
+            public IReadOnlyList<CellRef> Refs { get; init; }
+        }
+
+        public readonly struct CellRef
+        {
+            public string SpriteId { get; init; }
+            public URect Rect { get; init; }
+        }
+
+        public readonly struct Stats
+        {
+            public IReadOnlyList<SpriteStats> Sprites { get; init; }
+            public IReadOnlyList<AtlasStats> Atlases { get; init; }
+            public long SourceBytes { get; init; }
+            public long AtlasBytes { get; init; }
+            public long MeshBytes { get; init; }
+            public float CompressionRatio => SourceBytes / (float)(AtlasBytes + MeshBytes);
+        }
+
+        public readonly struct SpriteStats
+        {
+            public string Id { get; init; }
+            public int Units { get; init; }
+            public int Unique { get; init; }
+            public int Shared { get; init; }
+            public int Vertices { get; init; }
+            public int Indices { get; init; }
+            public long MeshBytes { get; init; }
+        }
+
+        public readonly struct AtlasStats
+        {
+            public uint Width { get; init; }
+            public uint Height { get; init; }
+            public int Units { get; init; }
+            public float Occupancy { get; init; }
+            public long WastedTexels { get; init; }
+        }
+
         public readonly struct DicedSprite
         {
             public string Id { get; init; }
@@ -90,8 +196,41 @@ namespace SpriteDicing
             public IReadOnlyList<Vertex> Vertices { get; init; }
             public IReadOnlyList<UV> UVs { get; init; }
             public IReadOnlyList<int> Indices { get; init; }
+            public Range OpaqueIndices { get; init; }
+            public Range TranslucentIndices { get; init; }
             public Rect Rect { get; init; }
             public Pivot Pivot { get; init; }
+            public uint SourceWidth { get; init; }
+            public uint SourceHeight { get; init; }
+            public URect PixelRect { get; init; }
+            public Pivot PixelPivot { get; init; }
+            public IReadOnlyDictionary<string, string> Metadata { get; init; }
+            public Border? Border { get; init; }
+            public IReadOnlyList<Slice> Slices { get; init; }
+            public IReadOnlyList<IReadOnlyList<Vertex>> Outlines { get; init; }
+            public IReadOnlyList<Pixel> Colors { get; init; }
+            public string AliasOf { get; init; }
+        }
+
+        public readonly struct Border
+        {
+            public uint Left { get; init; }
+            public uint Right { get; init; }
+            public uint Top { get; init; }
+            public uint Bottom { get; init; }
+        }
+
+        public enum Slice : byte
+        {
+            TopLeft,
+            Top,
+            TopRight,
+            Left,
+            Center,
+            Right,
+            BottomLeft,
+            Bottom,
+            BottomRight
         }
 
         public readonly struct Vertex
@@ -120,6 +259,14 @@ namespace SpriteDicing
             public float Height { get; init; }
         }
 
+        public readonly struct URect
+        {
+            public uint X { get; init; }
+            public uint Y { get; init; }
+            public uint Width { get; init; }
+            public uint Height { get; init; }
+        }
+
         public readonly struct Progress
         {
             public float Ratio { get; init; }
@@ -128,6 +275,18 @@ namespace SpriteDicing
 
         public delegate void ProgressCallback (Progress progress);
 
+        public delegate bool CancelCallback ();
+
+        public class DicingException : Exception
+        {
+            public uint Code { get; }
+
+            public DicingException (uint code, string message) : base(message)
+            {
+                Code = code;
+            }
+        }
+
         [StructLayout(LayoutKind.Sequential)]
         private struct CSourceSprite
         {
@@ -136,6 +295,30 @@ namespace SpriteDicing
             [MarshalAs(UnmanagedType.I1)]
             public bool has_pivot;
             public CPivot pivot;
+            public CSlice metadata;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool has_border;
+            public CBorder border;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool has_ppu;
+            public float ppu;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool has_trim_transparent;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool trim_transparent;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool has_padding;
+            public uint padding;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool has_sequence;
+            public IntPtr sequence;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct CMetadataEntry
+        {
+            public IntPtr key;
+            public IntPtr value;
         }
 
         [StructLayout(LayoutKind.Sequential)]
@@ -151,32 +334,113 @@ namespace SpriteDicing
         {
             public uint unit_size;
             public uint padding;
+            public uint block_alignment;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool grow_padding;
+            public uint mip_levels;
             public float uv_inset;
             [MarshalAs(UnmanagedType.I1)]
             public bool trim_transparent;
+            public byte alpha_crop;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool has_outline_tolerance;
+            public float outline_tolerance;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool dedupe_tints;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool alias_duplicates;
             public uint atlas_size_limit;
             [MarshalAs(UnmanagedType.I1)]
             public bool atlas_square;
             [MarshalAs(UnmanagedType.I1)]
             public bool atlas_pot;
+            public byte unit_order;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool collect_stats;
             public float ppu;
             public CPivot pivot;
+            public CCoordinates coordinates;
             [MarshalAs(UnmanagedType.I1)]
             public bool has_progress_callback;
             public CProgressCallback progress_callback;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool has_cancel_callback;
+            public CCancelCallback cancel_callback;
         }
 
         [StructLayout(LayoutKind.Sequential)]
         private struct CArtifacts
         {
             public CSlice atlases;
+            public CSlice mips;
+            public CSlice cells;
             public CSlice sprites;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool has_stats;
+            public CStats stats;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct CAtlasCell
+        {
+            public ulong atlas_index;
+            public ulong hash;
+            public CURect rect;
+            public CSlice refs;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct CCellRef
+        {
+            public IntPtr sprite_id;
+            public CURect rect;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct CStats
+        {
+            public CSlice sprites;
+            public CSlice atlases;
+            public ulong source_bytes;
+            public ulong atlas_bytes;
+            public ulong mesh_bytes;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct CSpriteStats
+        {
+            public IntPtr id;
+            public ulong units;
+            public ulong unique;
+            public ulong shared;
+            public ulong vertices;
+            public ulong indices;
+            public ulong mesh_bytes;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct CAtlasStats
+        {
+            public uint width;
+            public uint height;
+            public ulong units;
+            public float occupancy;
+            public ulong wasted_texels;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct CCoordinates
+        {
+            public byte y_axis;
+            public byte uv_origin;
+            public byte winding;
         }
 
         [StructLayout(LayoutKind.Sequential)]
         private struct CResult
         {
             public IntPtr error;
+            public uint code;
             public CArtifacts ok;
         }
 
@@ -188,8 +452,24 @@ namespace SpriteDicing
             public CSlice vertices;
             public CSlice uvs;
             public CSlice indices;
+            public CRange opaque_indices;
+            public CRange translucent_indices;
             public CRect rect;
             public CPivot pivot;
+            public uint source_width;
+            public uint source_height;
+            public CURect pixel_rect;
+            public CPivot pixel_pivot;
+            public CSlice metadata;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool has_border;
+            public CBorder border;
+            public CSlice slices;
+            public CSlice outlines;
+            public CSlice colors;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool has_alias;
+            public IntPtr alias_of;
         }
 
         [StructLayout(LayoutKind.Sequential)]
@@ -215,6 +495,31 @@ namespace SpriteDicing
             public float height;
         }
 
+        [StructLayout(LayoutKind.Sequential)]
+        private struct CURect
+        {
+            public uint x;
+            public uint y;
+            public uint width;
+            public uint height;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct CRange
+        {
+            public ulong start;
+            public ulong end;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct CBorder
+        {
+            public uint left;
+            public uint right;
+            public uint top;
+            public uint bottom;
+        }
+
         [StructLayout(LayoutKind.Sequential)]
         private struct CPivot
         {
@@ -237,6 +542,8 @@ namespace SpriteDicing
         }
 
         private delegate void CProgressCallback (CProgress progress);
+        [return: MarshalAs(UnmanagedType.I1)]
+        private delegate bool CCancelCallback ();
 
         public static Artifacts Dice (IEnumerable<SourceSprite> sprites, Prefs prefs)
         {
@@ -247,11 +554,15 @@ namespace SpriteDicing
 
             var error = Marshal.PtrToStringUTF8(result.error);
             if (!string.IsNullOrEmpty(error))
-                throw new Exception(error);
+                throw new DicingException(result.code, error);
 
+            var atlases = MarshalAtlases(result.ok.atlases, pts);
             return new Artifacts(
-                MarshalAtlases(result.ok.atlases, pts),
+                atlases,
+                MarshalMips(result.ok.mips, atlases.Length, pts),
+                MarshalCells(result.ok.cells, atlases.Length, pts),
                 MarshalDicedSprites(result.ok.sprites, pts),
+                result.ok.has_stats ? MarshalStats(result.ok.stats, pts) : null,
                 pts
             );
         }
@@ -262,15 +573,32 @@ namespace SpriteDicing
         private static CPrefs MarshalPrefs (Prefs prefs) => new() {
             unit_size = prefs.UnitSize,
             padding = prefs.Padding,
+            block_alignment = prefs.BlockAlignment,
+            grow_padding = prefs.GrowPadding,
+            mip_levels = prefs.MipLevels,
             uv_inset = prefs.UVInset,
             trim_transparent = prefs.TrimTransparent,
+            alpha_crop = (byte)prefs.AlphaCrop,
+            has_outline_tolerance = prefs.OutlineTolerance.HasValue,
+            outline_tolerance = prefs.OutlineTolerance.GetValueOrDefault(),
+            dedupe_tints = prefs.DedupeTints,
+            alias_duplicates = prefs.AliasDuplicates,
             atlas_size_limit = prefs.AtlasSizeLimit,
             atlas_square = prefs.AtlasSquare,
             atlas_pot = prefs.AtlasPOT,
+            unit_order = (byte)prefs.UnitOrder,
+            collect_stats = prefs.CollectStats,
             pivot = MarshalPivot(prefs.Pivot),
             ppu = prefs.PPU,
+            coordinates = new CCoordinates {
+                y_axis = (byte)prefs.Coordinates.YAxis,
+                uv_origin = (byte)prefs.Coordinates.UVOrigin,
+                winding = (byte)prefs.Coordinates.Winding
+            },
             has_progress_callback = prefs.OnProgress != null,
-            progress_callback = p => prefs.OnProgress(MarshalProgress(p))
+            progress_callback = p => prefs.OnProgress(MarshalProgress(p)),
+            has_cancel_callback = prefs.OnCancel != null,
+            cancel_callback = () => prefs.OnCancel()
         };
 
         private static T[] MarshalSlice<T> (CSlice c, List<IntPtr> pts)
@@ -305,6 +633,32 @@ namespace SpriteDicing
             return atlasSlices.Select(s => MarshalTexture(s, pts)).ToArray();
         }
 
+        private static Texture[][] MarshalMips (CSlice c, int atlasCount, List<IntPtr> pts)
+        {
+            var mips = MarshalAtlases(c, pts);
+            var levels = atlasCount > 0 ? mips.Length / atlasCount : 0;
+            return Enumerable.Range(0, atlasCount).Select(i => mips.Skip(i * levels).Take(levels).ToArray()).ToArray();
+        }
+
+        private static AtlasCell[][] MarshalCells (CSlice c, int atlasCount, List<IntPtr> pts)
+        {
+            var cells = MarshalSlice<CAtlasCell>(c, pts);
+            return Enumerable.Range(0, atlasCount).Select(i => cells
+                .Where(cell => (int)cell.atlas_index == i)
+                .Select(cell => MarshalCell(cell, pts)).ToArray()).ToArray();
+        }
+
+        private static AtlasCell MarshalCell (CAtlasCell c, List<IntPtr> pts) => new() {
+            Hash = c.hash,
+            Rect = MarshalURect(c.rect),
+            Refs = MarshalSlice<CCellRef>(c.refs, pts).Select(MarshalCellRef).ToArray()
+        };
+
+        private static CellRef MarshalCellRef (CCellRef c) => new() {
+            SpriteId = Marshal.PtrToStringUTF8(c.sprite_id),
+            Rect = MarshalURect(c.rect)
+        };
+
         private static CSlice MarshalSourceSprites (IEnumerable<SourceSprite> sources, List<GCHandle> pins)
         {
             var sprites = sources.Select(s => MarshalSourceSprite(s, pins)).ToArray();
@@ -318,17 +672,68 @@ namespace SpriteDicing
             pivot = new CPivot {
                 x = s.Pivot.GetValueOrDefault().X,
                 y = s.Pivot.GetValueOrDefault().Y
-            }
+            },
+            metadata = MarshalMetadata(s.Metadata, pins),
+            has_border = s.Border.HasValue,
+            border = MarshalBorder(s.Border.GetValueOrDefault()),
+            has_ppu = s.PPU.HasValue,
+            ppu = s.PPU.GetValueOrDefault(),
+            has_trim_transparent = s.TrimTransparent.HasValue,
+            trim_transparent = s.TrimTransparent.GetValueOrDefault(),
+            has_padding = s.Padding.HasValue,
+            padding = s.Padding.GetValueOrDefault(),
+            has_sequence = s.Sequence != null,
+            sequence = Marshal.StringToHGlobalAnsi(s.Sequence ?? "")
         };
 
+        private static CSlice MarshalMetadata (IReadOnlyDictionary<string, string> metadata, List<GCHandle> pins)
+        {
+            var entries = (metadata ?? new Dictionary<string, string>()).Select(kv => new CMetadataEntry {
+                key = Marshal.StringToHGlobalAnsi(kv.Key),
+                value = Marshal.StringToHGlobalAnsi(kv.Value)
+            }).ToArray();
+            return MarshalSlice(entries, pins);
+        }
+
         private static DicedSprite MarshalDicedSprite (CDicedSprite c, List<IntPtr> pts) => new() {
             Id = Marshal.PtrToStringUTF8(c.id),
             Atlas = (int)c.atlas,
             Vertices = MarshalSlice<CVertex>(c.vertices, pts).Select(MarshalVertex).ToArray(),
             UVs = MarshalSlice<CUv>(c.uvs, pts).Select(MarshalUV).ToArray(),
             Indices = MarshalIndices(c.indices),
+            OpaqueIndices = MarshalRange(c.opaque_indices),
+            TranslucentIndices = MarshalRange(c.translucent_indices),
             Rect = MarshalRect(c.rect),
-            Pivot = MarshalPivot(c.pivot)
+            Pivot = MarshalPivot(c.pivot),
+            SourceWidth = c.source_width,
+            SourceHeight = c.source_height,
+            PixelRect = MarshalURect(c.pixel_rect),
+            PixelPivot = MarshalPivot(c.pixel_pivot),
+            Metadata = MarshalSlice<CMetadataEntry>(c.metadata, pts).ToDictionary(
+                e => Marshal.PtrToStringUTF8(e.key),
+                e => Marshal.PtrToStringUTF8(e.value)),
+            Border = c.has_border ? MarshalBorder(c.border) : null,
+            Slices = MarshalSlice<byte>(c.slices, pts).Select(s => (Slice)s).ToArray(),
+            Outlines = MarshalSlice<CSlice>(c.outlines, pts).Select(o =>
+                MarshalSlice<CVertex>(o, pts).Select(MarshalVertex).ToArray()).ToArray(),
+            Colors = MarshalSlice<Pixel>(c.colors, pts),
+            AliasOf = c.has_alias ? Marshal.PtrToStringUTF8(c.alias_of) : null
+        };
+
+        private static Range MarshalRange (CRange c) => new((int)c.start, (int)c.end);
+
+        private static CBorder MarshalBorder (Border b) => new() {
+            left = b.Left,
+            right = b.Right,
+            top = b.Top,
+            bottom = b.Bottom
+        };
+
+        private static Border MarshalBorder (CBorder c) => new() {
+            Left = c.left,
+            Right = c.right,
+            Top = c.top,
+            Bottom = c.bottom
         };
 
         private static DicedSprite[] MarshalDicedSprites (CSlice c, List<IntPtr> pts)
@@ -337,6 +742,32 @@ namespace SpriteDicing
             return sprites.Select(s => MarshalDicedSprite(s, pts)).ToArray();
         }
 
+        private static Stats MarshalStats (CStats c, List<IntPtr> pts) => new() {
+            Sprites = MarshalSlice<CSpriteStats>(c.sprites, pts).Select(MarshalSpriteStats).ToArray(),
+            Atlases = MarshalSlice<CAtlasStats>(c.atlases, pts).Select(MarshalAtlasStats).ToArray(),
+            SourceBytes = (long)c.source_bytes,
+            AtlasBytes = (long)c.atlas_bytes,
+            MeshBytes = (long)c.mesh_bytes
+        };
+
+        private static SpriteStats MarshalSpriteStats (CSpriteStats c) => new() {
+            Id = Marshal.PtrToStringUTF8(c.id),
+            Units = (int)c.units,
+            Unique = (int)c.unique,
+            Shared = (int)c.shared,
+            Vertices = (int)c.vertices,
+            Indices = (int)c.indices,
+            MeshBytes = (long)c.mesh_bytes
+        };
+
+        private static AtlasStats MarshalAtlasStats (CAtlasStats c) => new() {
+            Width = c.width,
+            Height = c.height,
+            Units = (int)c.units,
+            Occupancy = c.occupancy,
+            WastedTexels = (long)c.wasted_texels
+        };
+
         private static Vertex MarshalVertex (CVertex c) => new() {
             X = c.x,
             Y = c.y
@@ -363,6 +794,13 @@ namespace SpriteDicing
             Height = c.height
         };
 
+        private static URect MarshalURect (CURect c) => new() {
+            X = c.x,
+            Y = c.y,
+            Width = c.width,
+            Height = c.height
+        };
+
         private static Pivot MarshalPivot (CPivot c) => new() {
             X = c.x,
             Y = c.y
@@ -29,7 +29,8 @@ namespace SpriteDicing
                 Native = new() {
                     Id = BuildID(texturePath),
                     Texture = BuildTexture(texturePath),
-                    Pivot = GetPivot(texturePath)
+                    Pivot = GetPivot(texturePath),
+                    Border = GetBorder(texturePath)
                 },
                 Texture = AssetDatabase.LoadAssetAtPath<Texture2D>(texturePath)
             };
@@ -54,6 +55,19 @@ namespace SpriteDicing
             return new Native.Pivot { X = pivot.x, Y = pivot.y };
         }
 
+        private Native.Border? GetBorder (string texturePath)
+        {
+            var sprite = AssetDatabase.LoadAssetAtPath<Sprite>(texturePath);
+            if (!sprite || sprite.border == Vector4.zero) return null;
+            // Texture rows are fed bottom to top, hence native top is the sprite's bottom.
+            return new Native.Border {
+                Left = (uint)sprite.border.x,
+                Right = (uint)sprite.border.z,
+                Top = (uint)sprite.border.y,
+                Bottom = (uint)sprite.border.w
+            };
+        }
+
         private Native.Texture BuildTexture (string texturePath)
         {
             EnsureReadable(texturePath);
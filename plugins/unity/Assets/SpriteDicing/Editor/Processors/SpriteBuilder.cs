@@ -35,7 +35,8 @@ namespace SpriteDicing
             var uvs = diced.UVs.Select(v => new Vector2(v.U, v.V)).ToArray();
             var triangles = diced.Indices.Select(i => (ushort)i).ToArray();
 
-            var sprite = CreateSprite(texture, pivot, rect);
+            var border = GetBorder(diced);
+            var sprite = CreateSprite(texture, pivot, rect, border);
             sprite.name = diced.Id;
             sprite.SetVertexCount(vertices.Length);
             sprite.SetIndices(new NativeArray<ushort>(triangles, Allocator.Temp));
@@ -45,10 +46,18 @@ namespace SpriteDicing
             return sprite;
         }
 
-        private Sprite CreateSprite (Texture texture, Vector2 pivot, Rect rect)
+        private static Vector4 GetBorder (Native.DicedSprite diced)
+        {
+            if (!diced.Border.HasValue) return Vector4.zero;
+            var border = diced.Border.Value;
+            // (left, bottom, right, top); native top is the sprite's bottom, see SourceLoader.
+            return new Vector4(border.Left, border.Top, border.Right, border.Bottom);
+        }
+
+        private Sprite CreateSprite (Texture texture, Vector2 pivot, Rect rect, Vector4 border)
         {
             // (texture, rect, pivot, pixelsPerUnit, extrude, meshType, border, generateFallbackPhysicsShape, secondaryTexture)
-            var args = new object[] { texture, rect, pivot, ppu, (uint)0, SpriteMeshType.Tight, Vector4.zero, false, null };
+            var args = new object[] { texture, rect, pivot, ppu, (uint)0, SpriteMeshType.Tight, border, false, null };
             return (Sprite)createSpriteMethod.Invoke(null, args);
         }
     }